@@ -0,0 +1,437 @@
+//! Sound effects and music, played in response to game events
+//!
+//! Each sound is read from a file named after it in the audio folder, as
+//! `<name>.ogg` or `<name>.wav`. Sounds without a file are left silent. With
+//! the `audio` feature off, or without a sound device, everything goes to
+//! [`NullAudio`].
+
+use crate::model::{Board, Event};
+
+#[cfg(feature = "audio")]
+pub use self::device::{AudioError, DeviceAudio};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sound {
+    Move,
+    Rotate,
+    Lock,
+    LineClear,
+    Tetris,
+    LevelUp,
+    GameOver,
+}
+
+/// Somewhere to send sounds to
+pub trait AudioBackend {
+    fn play(&mut self, sound: Sound);
+
+    /// Starts the music from the beginning, looping until it is stopped
+    fn start_music(&mut self);
+
+    fn stop_music(&mut self);
+
+    fn set_music_paused(&mut self, paused: bool);
+
+    /// Plays the music at `speed` times its normal tempo
+    fn set_music_speed(&mut self, speed: f32);
+}
+
+/// Plays nothing, for headless runs and machines without a sound device
+pub struct NullAudio;
+
+/// Turns game events into sounds, speeding the music up while the stack is
+/// high
+pub struct Audio {
+    backend: Box<dyn AudioBackend>,
+    fast: bool,
+}
+
+// The music speeds up once the stack is this many rows high, like the NES
+// switching to the fast version of the song
+const DANGER_HEIGHT: usize = 15;
+const FAST_MUSIC_SPEED: f32 = 1.25;
+
+impl Sound {
+    pub const ALL: [Sound; 7] = [
+        Sound::Move,
+        Sound::Rotate,
+        Sound::Lock,
+        Sound::LineClear,
+        Sound::Tetris,
+        Sound::LevelUp,
+        Sound::GameOver,
+    ];
+
+    /// The file name the sound is loaded from, without its extension
+    pub fn name(self) -> &'static str {
+        match self {
+            Sound::Move => "move",
+            Sound::Rotate => "rotate",
+            Sound::Lock => "lock",
+            Sound::LineClear => "line_clear",
+            Sound::Tetris => "tetris",
+            Sound::LevelUp => "level_up",
+            Sound::GameOver => "game_over",
+        }
+    }
+}
+
+impl AudioBackend for NullAudio {
+    fn play(&mut self, _: Sound) {}
+
+    fn start_music(&mut self) {}
+
+    fn stop_music(&mut self) {}
+
+    fn set_music_paused(&mut self, _: bool) {}
+
+    fn set_music_speed(&mut self, _: f32) {}
+}
+
+impl Audio {
+    pub fn new(backend: Box<dyn AudioBackend>) -> Self {
+        Audio {
+            backend,
+            fast: false,
+        }
+    }
+
+    pub fn start_music(&mut self) {
+        self.fast = false;
+        self.backend.set_music_speed(1.0);
+        self.backend.start_music();
+    }
+
+    pub fn stop_music(&mut self) {
+        self.backend.stop_music();
+    }
+
+    pub fn set_music_paused(&mut self, paused: bool) {
+        self.backend.set_music_paused(paused);
+    }
+
+    /// Plays whatever `event` sounds like, where `board` is the board after
+    /// it
+    pub fn handle(&mut self, event: &Event, board: &Board) {
+        match event {
+            // Soft drops and gravity are silent
+            Event::Moved { x, .. } if *x != 0 => self.backend.play(Sound::Move),
+            Event::Rotated { .. } => self.backend.play(Sound::Rotate),
+            Event::Locked { rows, .. } => self.backend.play(match rows.len() {
+                0 => Sound::Lock,
+                1..=3 => Sound::LineClear,
+                _ => Sound::Tetris,
+            }),
+            Event::LevelUp(_) => self.backend.play(Sound::LevelUp),
+            Event::ToppedOut => self.backend.play(Sound::GameOver),
+            _ => {}
+        }
+
+        // The stack only changes height when a piece locks or lines go
+        if let Event::Locked { .. } | Event::LinesCleared { .. } = event {
+            let fast = board.stack_height() >= DANGER_HEIGHT;
+            if fast != self.fast {
+                self.fast = fast;
+                self.backend
+                    .set_music_speed(if fast { FAST_MUSIC_SPEED } else { 1.0 });
+            }
+        }
+    }
+}
+
+#[cfg(feature = "audio")]
+mod device {
+    use super::{AudioBackend, Sound};
+    use rodio::{source::Buffered, Decoder, OutputStream, OutputStreamHandle, Sink, Source};
+    use std::{
+        fmt::Display,
+        fs::File,
+        io::BufReader,
+        path::{Path, PathBuf},
+    };
+
+    type Clip = Buffered<Decoder<BufReader<File>>>;
+
+    /// Plays through the default sound device
+    pub struct DeviceAudio {
+        // Sound stops when the stream is dropped
+        _stream: OutputStream,
+        handle: OutputStreamHandle,
+        sounds: Vec<Option<Clip>>,
+        music: Option<Clip>,
+        music_sink: Option<Sink>,
+        music_speed: f32,
+    }
+
+    #[derive(Debug)]
+    pub enum AudioError {
+        Device(rodio::StreamError),
+        Io {
+            path: PathBuf,
+            error: std::io::Error,
+        },
+        Decode {
+            path: PathBuf,
+            error: rodio::decoder::DecoderError,
+        },
+    }
+
+    const EXTENSIONS: [&str; 2] = ["ogg", "wav"];
+
+    const MUSIC_NAME: &str = "music";
+
+    // Finds and decodes `name` in `directory`, if there is a file for it
+    fn load(directory: &Path, name: &str) -> Result<Option<Clip>, AudioError> {
+        let path = match EXTENSIONS
+            .iter()
+            .map(|extension| directory.join(format!("{}.{}", name, extension)))
+            .find(|path| path.is_file())
+        {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(error) => return Err(AudioError::Io { path, error }),
+        };
+
+        match Decoder::new(BufReader::new(file)) {
+            Ok(decoder) => Ok(Some(decoder.buffered())),
+            Err(error) => Err(AudioError::Decode { path, error }),
+        }
+    }
+
+    impl DeviceAudio {
+        /// Opens the default sound device and loads every sound and the
+        /// music from `directory`
+        pub fn open(directory: &Path) -> Result<Self, AudioError> {
+            let (stream, handle) = OutputStream::try_default().map_err(AudioError::Device)?;
+
+            let mut sounds = Vec::with_capacity(Sound::ALL.len());
+            for sound in Sound::ALL {
+                sounds.push(load(directory, sound.name())?);
+            }
+
+            Ok(DeviceAudio {
+                _stream: stream,
+                handle,
+                sounds,
+                music: load(directory, MUSIC_NAME)?,
+                music_sink: None,
+                music_speed: 1.0,
+            })
+        }
+    }
+
+    impl AudioBackend for DeviceAudio {
+        fn play(&mut self, sound: Sound) {
+            if let Some(clip) = &self.sounds[sound as usize] {
+                // A sound that can't be played is just missed
+                let _ = self.handle.play_raw(clip.clone().convert_samples());
+            }
+        }
+
+        fn start_music(&mut self) {
+            let music = match &self.music {
+                Some(music) => music,
+                None => return,
+            };
+
+            // Dropping the old sink stops it
+            self.music_sink = Sink::try_new(&self.handle).ok();
+            if let Some(sink) = &self.music_sink {
+                sink.set_speed(self.music_speed);
+                sink.append(music.clone().repeat_infinite());
+            }
+        }
+
+        fn stop_music(&mut self) {
+            self.music_sink = None;
+        }
+
+        fn set_music_paused(&mut self, paused: bool) {
+            if let Some(sink) = &self.music_sink {
+                if paused {
+                    sink.pause();
+                } else {
+                    sink.play();
+                }
+            }
+        }
+
+        fn set_music_speed(&mut self, speed: f32) {
+            self.music_speed = speed;
+            if let Some(sink) = &self.music_sink {
+                sink.set_speed(speed);
+            }
+        }
+    }
+
+    impl Display for AudioError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                AudioError::Device(error) => error.fmt(f),
+                AudioError::Io { path, error } => {
+                    write!(f, "unable to open \"{}\": {}", path.display(), error)
+                }
+                AudioError::Decode { path, error } => {
+                    write!(f, "unable to decode \"{}\": {}", path.display(), error)
+                }
+            }
+        }
+    }
+
+    impl std::error::Error for AudioError {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Clear, PieceClass, TSpin, TileColor, BOARD_HEIGHT};
+    use std::{cell::RefCell, rc::Rc};
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Call {
+        Play(Sound),
+        Speed(f32),
+    }
+
+    /// Remembers what it was asked to play, in a list the test keeps a
+    /// handle to
+    struct RecordingAudio(Rc<RefCell<Vec<Call>>>);
+
+    impl AudioBackend for RecordingAudio {
+        fn play(&mut self, sound: Sound) {
+            self.0.borrow_mut().push(Call::Play(sound));
+        }
+
+        fn start_music(&mut self) {}
+
+        fn stop_music(&mut self) {}
+
+        fn set_music_paused(&mut self, _: bool) {}
+
+        fn set_music_speed(&mut self, speed: f32) {
+            self.0.borrow_mut().push(Call::Speed(speed));
+        }
+    }
+
+    fn recording_audio() -> (Audio, Rc<RefCell<Vec<Call>>>) {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        (Audio::new(Box::new(RecordingAudio(calls.clone()))), calls)
+    }
+
+    fn locked(lines: usize) -> Event {
+        Event::Locked {
+            class: PieceClass::I,
+            rows: (0..lines as isize).collect(),
+            clear: Clear {
+                lines,
+                t_spin: TSpin::None,
+                combo: 0,
+                back_to_back: false,
+                perfect_clear: false,
+                points: 0,
+            },
+        }
+    }
+
+    /// A board whose stack is `height` rows high
+    fn stack(height: usize) -> Board {
+        let mut board = Board::new();
+        for y in BOARD_HEIGHT - height..BOARD_HEIGHT {
+            board.set(0, y as isize, Some(TileColor::Red));
+        }
+        board
+    }
+
+    #[test]
+    fn events_play_their_sounds() {
+        let (mut audio, calls) = recording_audio();
+        let board = Board::new();
+        let events = [
+            Event::Spawned(PieceClass::T),
+            Event::Moved { x: -1, y: 0 },
+            // Soft drops and gravity
+            Event::Moved { x: 0, y: 1 },
+            Event::Rotated { clockwise: true },
+            Event::ScoreChanged(10),
+            locked(0),
+            locked(1),
+            locked(3),
+            locked(4),
+            Event::LevelUp(1),
+            Event::ToppedOut,
+        ];
+        for event in &events {
+            audio.handle(event, &board);
+        }
+
+        assert_eq!(
+            *calls.borrow(),
+            [
+                Call::Play(Sound::Move),
+                Call::Play(Sound::Rotate),
+                Call::Play(Sound::Lock),
+                Call::Play(Sound::LineClear),
+                Call::Play(Sound::LineClear),
+                Call::Play(Sound::Tetris),
+                Call::Play(Sound::LevelUp),
+                Call::Play(Sound::GameOver),
+            ]
+        );
+    }
+
+    #[test]
+    fn music_speeds_up_while_the_stack_is_high() {
+        let (mut audio, calls) = recording_audio();
+
+        audio.handle(&locked(0), &stack(DANGER_HEIGHT - 1));
+        assert_eq!(*calls.borrow(), [Call::Play(Sound::Lock)]);
+        calls.borrow_mut().clear();
+
+        // Only a change of speed is passed on
+        audio.handle(&locked(0), &stack(DANGER_HEIGHT));
+        audio.handle(&locked(0), &stack(DANGER_HEIGHT + 1));
+        assert_eq!(
+            *calls.borrow(),
+            [
+                Call::Play(Sound::Lock),
+                Call::Speed(FAST_MUSIC_SPEED),
+                Call::Play(Sound::Lock),
+            ]
+        );
+        calls.borrow_mut().clear();
+
+        // Moving doesn't change the stack, clearing lines does
+        audio.handle(&Event::Moved { x: 1, y: 0 }, &stack(0));
+        let cleared = Event::LinesCleared {
+            rows: vec![19],
+            clear: Clear {
+                lines: 1,
+                t_spin: TSpin::None,
+                combo: 0,
+                back_to_back: false,
+                perfect_clear: false,
+                points: 40,
+            },
+        };
+        audio.handle(&cleared, &stack(2));
+        assert_eq!(*calls.borrow(), [Call::Play(Sound::Move), Call::Speed(1.0)]);
+
+        // Starting the music again starts it at normal speed
+        calls.borrow_mut().clear();
+        audio.start_music();
+        assert_eq!(*calls.borrow(), [Call::Speed(1.0)]);
+        audio.handle(&locked(0), &stack(DANGER_HEIGHT));
+        assert_eq!(
+            *calls.borrow(),
+            [
+                Call::Speed(1.0),
+                Call::Play(Sound::Lock),
+                Call::Speed(FAST_MUSIC_SPEED),
+            ]
+        );
+    }
+}
@@ -0,0 +1,526 @@
+//! A live feed of game state for spectators, overlays and scoreboards
+//!
+//! The feed is JSON lines, written to a file or to every program connected to
+//! a local TCP port. Every line is an object with the format `version` and a
+//! `type`:
+//!
+//! - `start` when a game begins, with its `player`, `mode`, `ruleset`,
+//!   starting `level` and `seed`
+//! - `state` on each frame something happened, with the `player`, `frame`,
+//!   the `board` as 20 rows from the top of 10 characters each, the current
+//!   `piece` with its `class` and `cells`, the `next` queue, the `hold`
+//!   piece, `score`, `lines`, `level`, the `events` of that frame and whether
+//!   the game is `over`
+//!
+//! Cells hold the letter of the piece they came from, `X` for garbage or `.`
+//! when empty, and the piece's `cells` are `[x, y]` pairs counted from the
+//! top left. Players are numbered from 0, and are always 0 outside versus.
+//! Programs reading the feed should ignore fields they don't know, which may
+//! be added without changing the version.
+
+use crate::model::{Clear, Event, Game, PieceClass, TSpin, TileColor, BOARD_HEIGHT, BOARD_WIDTH};
+use std::{
+    fmt::Write as _,
+    fs::File,
+    io::{BufWriter, Write},
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    path::Path,
+};
+
+/// Where the feed goes, if anywhere
+pub struct Broadcast {
+    sink: Option<Sink>,
+    // The start line of each player's game, for spectators who connect late
+    starts: Vec<String>,
+}
+
+enum Sink {
+    File(BufWriter<File>),
+    Socket {
+        listener: TcpListener,
+        spectators: Vec<TcpStream>,
+    },
+}
+
+pub const VERSION: u32 = 1;
+
+fn class_name(class: PieceClass) -> char {
+    match class {
+        PieceClass::T => 'T',
+        PieceClass::J => 'J',
+        PieceClass::Z => 'Z',
+        PieceClass::O => 'O',
+        PieceClass::S => 'S',
+        PieceClass::L => 'L',
+        PieceClass::I => 'I',
+    }
+}
+
+// Every piece has its own color, so the color gives away the piece
+fn tile_name(tile: Option<TileColor>) -> char {
+    match tile {
+        None => '.',
+        Some(TileColor::Cyan) => 'I',
+        Some(TileColor::Blue) => 'O',
+        Some(TileColor::Orange) => 'T',
+        Some(TileColor::Yellow) => 'S',
+        Some(TileColor::Green) => 'Z',
+        Some(TileColor::Purple) => 'J',
+        Some(TileColor::Red) => 'L',
+        Some(TileColor::Garbage) => 'X',
+    }
+}
+
+fn t_spin_name(t_spin: TSpin) -> &'static str {
+    match t_spin {
+        TSpin::None => "none",
+        TSpin::Mini => "mini",
+        TSpin::Full => "full",
+    }
+}
+
+fn write_clear(line: &mut String, clear: &Clear) {
+    let _ = write!(
+        line,
+        r#""lines":{},"t_spin":"{}","combo":{},"back_to_back":{},"perfect_clear":{},"points":{}"#,
+        clear.lines,
+        t_spin_name(clear.t_spin),
+        clear.combo,
+        clear.back_to_back,
+        clear.perfect_clear,
+        clear.points
+    );
+}
+
+fn write_event(line: &mut String, event: &Event) {
+    // Writing to a string can't fail
+    let _ = match event {
+        Event::Spawned(class) => write!(
+            line,
+            r#"{{"type":"spawned","class":"{}"}}"#,
+            class_name(*class)
+        ),
+        Event::Moved { x, y } => write!(line, r#"{{"type":"moved","x":{},"y":{}}}"#, x, y),
+        Event::Rotated { clockwise } => {
+            write!(line, r#"{{"type":"rotated","clockwise":{}}}"#, clockwise)
+        }
+        Event::Held(class) => write!(
+            line,
+            r#"{{"type":"held","class":"{}"}}"#,
+            class_name(*class)
+        ),
+        Event::Locked { class, clear, .. } => {
+            let _ = write!(
+                line,
+                r#"{{"type":"locked","class":"{}","#,
+                class_name(*class)
+            );
+            write_clear(line, clear);
+            write!(line, "}}")
+        }
+        Event::LinesCleared { clear, .. } => {
+            line.push_str(r#"{"type":"lines_cleared","#);
+            write_clear(line, clear);
+            write!(line, "}}")
+        }
+        Event::GarbageAdded(rows) => write!(line, r#"{{"type":"garbage_added","rows":{}}}"#, rows),
+        Event::LevelUp(level) => write!(line, r#"{{"type":"level_up","level":{}}}"#, level),
+        Event::ScoreChanged(score) => {
+            write!(line, r#"{{"type":"score_changed","score":{}}}"#, score)
+        }
+        Event::ToppedOut => write!(line, r#"{{"type":"topped_out"}}"#),
+    };
+}
+
+impl Broadcast {
+    /// Publishes nothing
+    pub fn off() -> Self {
+        Broadcast {
+            sink: None,
+            starts: Vec::new(),
+        }
+    }
+
+    /// Writes the feed to a new file at `path`
+    pub fn to_file<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        Ok(Broadcast {
+            sink: Some(Sink::File(BufWriter::new(File::create(path)?))),
+            starts: Vec::new(),
+        })
+    }
+
+    /// Sends the feed to every program that connects to `port` on this
+    /// machine
+    pub fn serve(port: u16) -> std::io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        listener.set_nonblocking(true)?;
+
+        Ok(Broadcast {
+            sink: Some(Sink::Socket {
+                listener,
+                spectators: Vec::new(),
+            }),
+            starts: Vec::new(),
+        })
+    }
+
+    /// Announces a new game for `player`. Player 0's game starts first, so
+    /// that also forgets the games before it.
+    pub fn start(&mut self, player: usize, game: &Game) {
+        if self.sink.is_none() {
+            return;
+        }
+
+        let line = format!(
+            r#"{{"version":{},"type":"start","player":{},"mode":"{}","ruleset":"{}","level":{},"seed":{}}}"#,
+            VERSION,
+            player,
+            game.mode().name(),
+            game.ruleset().name(),
+            game.level(),
+            game.seed()
+        );
+
+        if player == 0 {
+            self.starts.clear();
+        }
+        if self.starts.len() <= player {
+            self.starts.resize(player + 1, String::new());
+        }
+        self.starts[player] = line.clone();
+        self.send(&line);
+    }
+
+    /// Publishes `player`'s game after `frame`, if any `events` happened on
+    /// it or the game is `over`
+    pub fn publish(
+        &mut self,
+        player: usize,
+        game: &Game,
+        frame: usize,
+        events: &[Event],
+        over: bool,
+    ) {
+        if self.sink.is_none() || (events.is_empty() && !over) {
+            return;
+        }
+
+        let mut line = format!(
+            r#"{{"version":{},"type":"state","player":{},"frame":{},"board":["#,
+            VERSION, player, frame
+        );
+
+        let board = game.board();
+        for y in 0..BOARD_HEIGHT as isize {
+            if y > 0 {
+                line.push(',');
+            }
+            line.push('"');
+            for x in 0..BOARD_WIDTH as isize {
+                line.push(tile_name(board.get(x, y).unwrap()));
+            }
+            line.push('"');
+        }
+
+        line.push_str(r#"],"piece":"#);
+        match game.current_piece() {
+            Some(piece) => {
+                let _ = write!(
+                    line,
+                    r#"{{"class":"{}","cells":["#,
+                    class_name(piece.class())
+                );
+                for i in 0..4 {
+                    let (x, y) = piece.get_tile_position(i);
+                    let separator = if i > 0 { "," } else { "" };
+                    let _ = write!(line, "{}[{},{}]", separator, x, y);
+                }
+                line.push_str("]}");
+            }
+            None => line.push_str("null"),
+        }
+
+        line.push_str(r#","next":["#);
+        for (i, class) in game.next_queue().iter().enumerate() {
+            let separator = if i > 0 { "," } else { "" };
+            let _ = write!(line, r#"{}"{}""#, separator, class_name(*class));
+        }
+
+        line.push_str(r#"],"hold":"#);
+        match game.held_piece() {
+            Some(piece) => {
+                let _ = write!(line, r#""{}""#, class_name(piece.class()));
+            }
+            None => line.push_str("null"),
+        }
+
+        let _ = write!(
+            line,
+            r#","score":{},"lines":{},"level":{},"events":["#,
+            game.score(),
+            game.total_lines(),
+            game.level()
+        );
+        for (i, event) in events.iter().enumerate() {
+            if i > 0 {
+                line.push(',');
+            }
+            write_event(&mut line, event);
+        }
+        let _ = write!(line, r#"],"over":{}}}"#, over);
+
+        self.send(&line);
+    }
+
+    fn send(&mut self, line: &str) {
+        match &mut self.sink {
+            Some(Sink::File(file)) => {
+                // Flushing every line lets readers follow the file as it grows
+                let result = writeln!(file, "{}", line).and_then(|_| file.flush());
+                if let Err(error) = result {
+                    eprintln!("Unable to write the broadcast, stopping it: {}", error);
+                    self.sink = None;
+                }
+            }
+            Some(Sink::Socket {
+                listener,
+                spectators,
+            }) => {
+                while let Ok((mut stream, _)) = listener.accept() {
+                    // Spectators joining partway through still learn what
+                    // is being played
+                    let caught_up = stream.set_nonblocking(true).is_ok()
+                        && self
+                            .starts
+                            .iter()
+                            .filter(|start| !start.is_empty())
+                            .all(|start| writeln!(stream, "{}", start).is_ok());
+                    if caught_up {
+                        spectators.push(stream);
+                    }
+                }
+
+                // Spectators who leave or fall too far behind are dropped
+                // rather than holding up the game
+                spectators.retain_mut(|stream| writeln!(stream, "{}", line).is_ok());
+            }
+            None => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{GameMode, PieceGenerator, RandomizerKind, Ruleset, DEFAULT_DIG_ROWS};
+    use std::{
+        io::{BufRead, BufReader},
+        time::Duration,
+    };
+
+    /// Checks `line` is a single object with its brackets and quotes paired.
+    /// Nothing in the feed is escaped, so counting them is enough.
+    fn assert_object(line: &str) {
+        assert!(line.starts_with('{') && line.ends_with('}'), "{}", line);
+        assert_eq!(
+            line.matches('{').count(),
+            line.matches('}').count(),
+            "{}",
+            line
+        );
+        assert_eq!(
+            line.matches('[').count(),
+            line.matches(']').count(),
+            "{}",
+            line
+        );
+        assert_eq!(line.matches('"').count() % 2, 0, "{}", line);
+    }
+
+    /// The items of the flat array `key` in `line`, as written
+    fn array<'a>(line: &'a str, key: &str) -> Vec<&'a str> {
+        let start = format!("\"{}\":[", key);
+        let (_, items) = line
+            .split_once(&start)
+            .unwrap_or_else(|| panic!("no {} in {}", key, line));
+        let (items, _) = items.split_once(']').unwrap();
+        if items.is_empty() {
+            Vec::new()
+        } else {
+            items.split(',').collect()
+        }
+    }
+
+    fn new_game(mode: GameMode, seed: u32) -> Game {
+        Game::new(
+            mode,
+            Ruleset::Modern,
+            3,
+            DEFAULT_DIG_ROWS,
+            PieceGenerator::new(seed, RandomizerKind::SevenBag, 2),
+        )
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("quadris-broadcast-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn state_lines_have_the_documented_fields() {
+        let path = temp_path("state");
+        let mut broadcast = Broadcast::to_file(&path).unwrap();
+        let mut game = new_game(GameMode::Dig, 5);
+        let events = game.take_events();
+        broadcast.start(0, &game);
+        broadcast.publish(0, &game, 1, &events, false);
+        // Nothing happened, so nothing is sent
+        broadcast.publish(0, &game, 2, &[], false);
+        drop(broadcast);
+
+        let feed = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let lines: Vec<_> = feed.lines().collect();
+        assert_eq!(lines.len(), 2);
+        lines.iter().for_each(|line| assert_object(line));
+
+        let start = lines[0];
+        assert!(start.starts_with(&format!(r#"{{"version":{},"type":"start","#, VERSION)));
+        for field in [
+            r#""player":0"#,
+            r#""mode":"dig""#,
+            r#""ruleset":"modern""#,
+            r#""level":3"#,
+            r#""seed":5"#,
+        ] {
+            assert!(start.contains(field), "no {} in {}", field, start);
+        }
+
+        let state = lines[1];
+        assert!(state.starts_with(&format!(r#"{{"version":{},"type":"state","#, VERSION)));
+        for field in [
+            r#""player":0"#,
+            r#""frame":1"#,
+            r#""hold":null"#,
+            r#""score":0"#,
+            r#""lines":0"#,
+            r#""level":3"#,
+            r#""over":false"#,
+        ] {
+            assert!(state.contains(field), "no {} in {}", field, state);
+        }
+
+        let board = array(state, "board");
+        assert_eq!(board.len(), BOARD_HEIGHT);
+        for (y, row) in board.iter().enumerate() {
+            let row = row.strip_prefix('"').unwrap().strip_suffix('"').unwrap();
+            assert_eq!(row.len(), BOARD_WIDTH);
+            // Dig starts with garbage in the bottom half, each row with a hole
+            if y < BOARD_HEIGHT / 2 {
+                assert_eq!(row, "..........");
+            } else {
+                assert_eq!(row.matches('X').count(), BOARD_WIDTH - 1);
+                assert_eq!(row.matches('.').count(), 1);
+            }
+        }
+
+        let piece = game.current_piece().unwrap();
+        let cells: Vec<_> = (0..4)
+            .map(|i| {
+                let (x, y) = piece.get_tile_position(i);
+                format!("[{},{}]", x, y)
+            })
+            .collect();
+        let piece_field = format!(
+            r#""piece":{{"class":"{}","cells":[{}]}}"#,
+            class_name(piece.class()),
+            cells.join(",")
+        );
+        assert!(
+            state.contains(&piece_field),
+            "no {} in {}",
+            piece_field,
+            state
+        );
+
+        let next: Vec<_> = game
+            .next_queue()
+            .iter()
+            .map(|class| format!("\"{}\"", class_name(*class)))
+            .collect();
+        assert_eq!(array(state, "next"), next);
+
+        let events = format!(
+            r#""events":[{{"type":"spawned","class":"{}"}},{{"type":"garbage_added","rows":10}}]"#,
+            class_name(piece.class())
+        );
+        assert!(state.contains(&events), "no {} in {}", events, state);
+    }
+
+    #[test]
+    fn every_event_is_an_object_with_a_type() {
+        let clear = Clear {
+            lines: 2,
+            t_spin: TSpin::Mini,
+            combo: 1,
+            back_to_back: true,
+            perfect_clear: false,
+            points: 600,
+        };
+        let events = [
+            Event::Spawned(PieceClass::T),
+            Event::Moved { x: -1, y: 0 },
+            Event::Rotated { clockwise: true },
+            Event::Held(PieceClass::I),
+            Event::Locked {
+                class: PieceClass::T,
+                rows: vec![18, 19],
+                clear,
+            },
+            Event::LinesCleared {
+                rows: vec![18, 19],
+                clear,
+            },
+            Event::GarbageAdded(3),
+            Event::LevelUp(4),
+            Event::ScoreChanged(600),
+            Event::ToppedOut,
+        ];
+
+        for event in &events {
+            let mut line = String::new();
+            write_event(&mut line, event);
+            assert_object(&line);
+            assert!(line.starts_with(r#"{"type":""#), "{}", line);
+        }
+    }
+
+    #[test]
+    fn late_spectators_only_hear_about_current_games() {
+        let mut broadcast = Broadcast::serve(0).unwrap();
+        let address = match &broadcast.sink {
+            Some(Sink::Socket { listener, .. }) => listener.local_addr().unwrap(),
+            _ => unreachable!(),
+        };
+
+        // A versus round, then a game on its own
+        broadcast.start(0, &new_game(GameMode::Marathon, 1));
+        broadcast.start(1, &new_game(GameMode::Marathon, 1));
+        let game = new_game(GameMode::Sprint, 2);
+        broadcast.start(0, &game);
+
+        let spectator = TcpStream::connect(address).unwrap();
+        spectator
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+        // The spectator is let in when the next line goes out
+        broadcast.publish(0, &game, 1, &[], true);
+
+        let mut lines = BufReader::new(spectator).lines();
+        let start = lines.next().unwrap().unwrap();
+        assert!(start.contains(r#""type":"start""#), "{}", start);
+        assert!(start.contains(r#""mode":"sprint""#), "{}", start);
+        let state = lines.next().unwrap().unwrap();
+        assert!(state.contains(r#""type":"state""#), "{}", state);
+        assert!(state.contains(r#""over":true"#), "{}", state);
+    }
+}
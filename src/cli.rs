@@ -0,0 +1,411 @@
+//! Options given on the command line
+//!
+//! Every option takes a value, written either as the next argument or after
+//! an `=`, as in `--level 9` or `--level=9`.
+
+use crate::{
+    controller::{Choices, MAX_LEVEL},
+    model::{GameMode, RandomizerKind, Ruleset},
+    replay::{Replay, ReplayError},
+};
+use std::{fmt::Display, path::PathBuf};
+
+pub enum Command {
+    Run(Options),
+    Help,
+}
+
+pub struct Options {
+    /// The menu starts with these picked
+    pub choices: Choices,
+    /// Replaces the randomizer from the config file for this run only
+    pub randomizer: Option<RandomizerKind>,
+    /// Played as soon as the game opens
+    pub replay: Option<Replay>,
+    /// Plays versus over the network instead of opening the menu
+    pub netplay: Option<Netplay>,
+    /// Where to publish the state of every game as it is played
+    pub broadcast: Option<BroadcastTarget>,
+    /// The window size asked for, as width and height in pixels
+    pub window_size: Option<(u32, u32)>,
+    /// Where live games are recorded, and what R on the menu plays back
+    pub record_path: PathBuf,
+    pub config_path: PathBuf,
+}
+
+/// Which end of a network game to be
+pub enum Netplay {
+    /// Wait for the other player on this port, with the settings given here
+    Host(u16),
+    /// Join the game at this address, as `host:port`
+    Connect(String),
+}
+
+/// Where the spectator feed goes
+pub enum BroadcastTarget {
+    File(PathBuf),
+    /// Every program connected to this port on this machine
+    Port(u16),
+}
+
+#[derive(Debug)]
+pub enum CliError {
+    UnknownOption(String),
+    MissingValue(String),
+    InvalidValue {
+        option: String,
+        value: String,
+        expected: String,
+    },
+    Replay { path: PathBuf, error: ReplayError },
+    Conflict(&'static str, &'static str),
+}
+
+pub const USAGE: &str = "\
+Usage: tetris_clone [OPTIONS]
+
+Options:
+  --mode <MODE>         marathon, b_type, sprint, ultra or dig
+  --level <LEVEL>       Starting level picked on the menu, from 0 to 19
+  --seed <SEED>         Seed for every game, from 0 to 4294967295
+  --ruleset <RULESET>   classic or modern
+  --randomizer <NAME>   seven_bag, nes, uniform, fourteen_bag or tgm
+  --replay <PATH>       Play back a replay file
+  --record <PATH>       Record live games to PATH [default: ./last_replay.qrp]
+  --config <PATH>       Read and write settings at PATH [default: ./config.txt]
+  --window-size <WxH>   Window size in pixels, such as 1280x720
+  --host <PORT>         Wait for a network versus game on PORT
+  --connect <ADDRESS>   Join the network versus game at ADDRESS, as host:port
+  --broadcast <PATH>    Write the state of each game to PATH as JSON lines
+  --broadcast-port <PORT>
+                        Send the state of each game to programs connecting to
+                        PORT on this machine
+  --help                Print this message";
+
+const DEFAULT_RECORD_PATH: &str = "./last_replay.qrp";
+const DEFAULT_CONFIG_PATH: &str = "./config.txt";
+
+fn invalid(option: &str, value: &str, expected: String) -> CliError {
+    CliError::InvalidValue {
+        option: option.to_owned(),
+        value: value.to_owned(),
+        expected,
+    }
+}
+
+fn parse_window_size(value: &str) -> Option<(u32, u32)> {
+    let (width, height) = value.split_once(['x', 'X'])?;
+    match (width.parse(), height.parse()) {
+        (Ok(width), Ok(height)) if width > 0 && height > 0 => Some((width, height)),
+        _ => None,
+    }
+}
+
+impl Command {
+    /// Parses the arguments after the program name
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, CliError> {
+        let mut options = Options::default();
+        let mut replay_path = None;
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if arg == "--help" || arg == "-h" {
+                return Ok(Command::Help);
+            }
+
+            let (option, value) = match arg.split_once('=') {
+                Some((option, value)) => (option.to_owned(), value.to_owned()),
+                None => {
+                    if !arg.starts_with("--") {
+                        return Err(CliError::UnknownOption(arg));
+                    }
+
+                    match args.next() {
+                        Some(value) => (arg, value),
+                        None => return Err(CliError::MissingValue(arg)),
+                    }
+                }
+            };
+
+            match option.as_str() {
+                "--mode" => {
+                    options.choices.mode = GameMode::from_name(&value).ok_or_else(|| {
+                        let names: Vec<&str> = GameMode::ALL.iter().map(|m| m.name()).collect();
+                        invalid(&option, &value, format!("one of {}", names.join(", ")))
+                    })?
+                }
+                "--level" => {
+                    options.choices.level = match value.parse() {
+                        Ok(level) if level <= MAX_LEVEL => level,
+                        _ => {
+                            return Err(invalid(
+                                &option,
+                                &value,
+                                format!("a level from 0 to {}", MAX_LEVEL),
+                            ))
+                        }
+                    }
+                }
+                "--seed" => {
+                    options.choices.seed = Some(value.parse().map_err(|_| {
+                        invalid(
+                            &option,
+                            &value,
+                            format!("a number from 0 to {}", u32::MAX),
+                        )
+                    })?)
+                }
+                "--ruleset" => {
+                    options.choices.ruleset = Ruleset::from_name(&value).ok_or_else(|| {
+                        let names: Vec<&str> = Ruleset::ALL.iter().map(|r| r.name()).collect();
+                        invalid(&option, &value, format!("one of {}", names.join(", ")))
+                    })?
+                }
+                "--randomizer" => {
+                    options.randomizer = Some(RandomizerKind::from_name(&value).ok_or_else(
+                        || {
+                            let names: Vec<&str> =
+                                RandomizerKind::ALL.iter().map(|kind| kind.name()).collect();
+                            invalid(&option, &value, format!("one of {}", names.join(", ")))
+                        },
+                    )?)
+                }
+                "--replay" => replay_path = Some(PathBuf::from(value)),
+                "--record" => options.record_path = PathBuf::from(value),
+                "--config" => options.config_path = PathBuf::from(value),
+                "--window-size" => {
+                    options.window_size = Some(parse_window_size(&value).ok_or_else(|| {
+                        invalid(
+                            &option,
+                            &value,
+                            "a width and height in pixels, as WIDTHxHEIGHT".to_owned(),
+                        )
+                    })?)
+                }
+                "--host" => {
+                    if let Some(Netplay::Connect(_)) = options.netplay {
+                        return Err(CliError::Conflict("--host", "--connect"));
+                    }
+                    let port = value.parse().map_err(|_| {
+                        invalid(&option, &value, format!("a port from 0 to {}", u16::MAX))
+                    })?;
+                    options.netplay = Some(Netplay::Host(port))
+                }
+                "--connect" => {
+                    if let Some(Netplay::Host(_)) = options.netplay {
+                        return Err(CliError::Conflict("--host", "--connect"));
+                    }
+                    options.netplay = Some(Netplay::Connect(value))
+                }
+                "--broadcast" => {
+                    if let Some(BroadcastTarget::Port(_)) = options.broadcast {
+                        return Err(CliError::Conflict("--broadcast", "--broadcast-port"));
+                    }
+                    options.broadcast = Some(BroadcastTarget::File(PathBuf::from(value)))
+                }
+                "--broadcast-port" => {
+                    if let Some(BroadcastTarget::File(_)) = options.broadcast {
+                        return Err(CliError::Conflict("--broadcast", "--broadcast-port"));
+                    }
+                    let port = value.parse().map_err(|_| {
+                        invalid(&option, &value, format!("a port from 0 to {}", u16::MAX))
+                    })?;
+                    options.broadcast = Some(BroadcastTarget::Port(port))
+                }
+                _ => return Err(CliError::UnknownOption(option)),
+            }
+
+            // Only one thing can happen when the window opens
+            if replay_path.is_some() && options.netplay.is_some() {
+                let other = match options.netplay {
+                    Some(Netplay::Host(_)) => "--host",
+                    _ => "--connect",
+                };
+                return Err(CliError::Conflict("--replay", other));
+            }
+        }
+
+        // The replay is read now so a bad file is reported before the window
+        // opens
+        if let Some(path) = replay_path {
+            options.replay =
+                Some(Replay::load(&path).map_err(|error| CliError::Replay { path, error })?);
+        }
+
+        Ok(Command::Run(options))
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            choices: Choices::default(),
+            randomizer: None,
+            replay: None,
+            netplay: None,
+            broadcast: None,
+            window_size: None,
+            record_path: PathBuf::from(DEFAULT_RECORD_PATH),
+            config_path: PathBuf::from(DEFAULT_CONFIG_PATH),
+        }
+    }
+}
+
+impl Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CliError::UnknownOption(option) => write!(f, "unknown option \"{}\"", option),
+            CliError::MissingValue(option) => write!(f, "{} needs a value", option),
+            CliError::InvalidValue {
+                option,
+                value,
+                expected,
+            } => write!(f, "invalid {} \"{}\": expected {}", option, value, expected),
+            CliError::Replay { path, error } => {
+                write!(f, "unable to load replay \"{}\": {}", path.display(), error)
+            }
+            CliError::Conflict(first, second) => {
+                write!(f, "{} can't be used with {}", first, second)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, CliError> {
+        Command::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn options(args: &[&str]) -> Options {
+        match parse(args) {
+            Ok(Command::Run(options)) => options,
+            Ok(Command::Help) => panic!("{:?} asked for help", args),
+            Err(error) => panic!("{:?} failed: {}", args, error),
+        }
+    }
+
+    fn error(args: &[&str]) -> CliError {
+        match parse(args) {
+            Ok(_) => panic!("{:?} was accepted", args),
+            Err(error) => error,
+        }
+    }
+
+    #[test]
+    fn value_after_equals_or_as_next_argument() {
+        for args in [
+            &["--level=9", "--seed=42", "--ruleset=classic"][..],
+            &["--level", "9", "--seed", "42", "--ruleset", "classic"],
+        ] {
+            let options = options(args);
+            assert_eq!(options.choices.level, 9);
+            assert_eq!(options.choices.seed, Some(42));
+            assert_eq!(options.choices.ruleset, Ruleset::Classic);
+        }
+
+        // Only the first `=` splits, so paths may contain more
+        let options = options(&["--record=replays/a=b.qrp"]);
+        assert_eq!(options.record_path, PathBuf::from("replays/a=b.qrp"));
+    }
+
+    #[test]
+    fn window_size() {
+        assert_eq!(
+            options(&["--window-size", "1280x720"]).window_size,
+            Some((1280, 720))
+        );
+        assert_eq!(
+            options(&["--window-size=800X600"]).window_size,
+            Some((800, 600))
+        );
+        assert_eq!(options(&[]).window_size, None);
+
+        for value in ["1280", "1280x", "x720", "0x720", "1280x-1", "wide x tall"] {
+            assert!(
+                matches!(
+                    error(&["--window-size", value]),
+                    CliError::InvalidValue { option, .. } if option == "--window-size"
+                ),
+                "{} was accepted",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn missing_value() {
+        assert!(matches!(
+            error(&["--seed"]),
+            CliError::MissingValue(option) if option == "--seed"
+        ));
+    }
+
+    #[test]
+    fn level_out_of_range() {
+        let level = (MAX_LEVEL + 1).to_string();
+        assert!(matches!(
+            error(&["--level", &level]),
+            CliError::InvalidValue { option, value, .. } if option == "--level" && value == level
+        ));
+        assert!(matches!(
+            error(&["--level=-1"]),
+            CliError::InvalidValue { .. }
+        ));
+        assert_eq!(
+            options(&["--level", &MAX_LEVEL.to_string()]).choices.level,
+            MAX_LEVEL
+        );
+    }
+
+    #[test]
+    fn unknown_option() {
+        assert!(matches!(
+            error(&["--speed", "3"]),
+            CliError::UnknownOption(option) if option == "--speed"
+        ));
+        assert!(matches!(
+            error(&["level"]),
+            CliError::UnknownOption(option) if option == "level"
+        ));
+    }
+
+    #[test]
+    fn conflicting_options() {
+        for (args, conflict) in [
+            (
+                &["--replay", "a.qrp", "--host", "7000"][..],
+                ("--replay", "--host"),
+            ),
+            (
+                &["--connect=localhost:7000", "--replay=a.qrp"],
+                ("--replay", "--connect"),
+            ),
+            (
+                &["--host", "7000", "--connect", "localhost:7000"],
+                ("--host", "--connect"),
+            ),
+            (
+                &["--connect", "localhost:7000", "--host", "7000"],
+                ("--host", "--connect"),
+            ),
+            (
+                &["--broadcast", "feed.jsonl", "--broadcast-port", "7001"],
+                ("--broadcast", "--broadcast-port"),
+            ),
+            (
+                &["--broadcast-port", "7001", "--broadcast", "feed.jsonl"],
+                ("--broadcast", "--broadcast-port"),
+            ),
+        ] {
+            match error(args) {
+                CliError::Conflict(first, second) => assert_eq!((first, second), conflict),
+                other => panic!("{:?} gave {}", args, other),
+            }
+        }
+    }
+}
@@ -0,0 +1,473 @@
+//! Player settings, read from a `name = value` text file
+//!
+//! Blank lines and lines starting with `#` are ignored. Each action names one
+//! or more keys separated by commas, either by name (`A`, `7`, `LEFT`,
+//! `SPACE`, ...) or as a virtual key code such as `0x25`, and the versus
+//! bindings are the same actions prefixed with `p1_` or `p2_`. The DAS delays
+//! are counted in frames.
+
+use crate::model::{
+    Action, AttackTable, DasSettings, RandomizerKind, DEFAULT_DIG_ROWS, MAX_DIG_ROWS,
+    MAX_NEXT_QUEUE_LENGTH,
+};
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{BufRead, BufReader, Write},
+    path::Path,
+};
+
+pub struct KeyBindings {
+    keys: [Vec<u8>; Action::ALL.len()],
+}
+
+pub struct Config {
+    pub bindings: KeyBindings,
+    /// Player one's keys then player two's, for versus
+    pub versus_bindings: [KeyBindings; 2],
+    pub attack: AttackTable,
+    pub das: DasSettings,
+    pub next_queue_length: usize,
+    /// Garbage rows Dig starts with
+    pub dig_rows: usize,
+    pub randomizer: RandomizerKind,
+    pub level_display: LevelDisplay,
+    pub score_display: ScoreDisplay,
+}
+
+/// How the two-digit level counter shows levels past 29
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LevelDisplay {
+    /// The last two decimal digits of the level
+    Decimal,
+    /// The digits the NES shows, including its letters past level 29
+    Nes,
+}
+
+/// How many digits the score counter has
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScoreDisplay {
+    /// Six digits, stopping at 999999 as on the NES
+    Classic,
+    /// Seven digits, for scores above a million
+    Modern,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(std::io::Error),
+    Invalid { line: usize, message: String },
+    InvalidDas,
+}
+
+const KEY_NAMES: &[(&str, u8)] = &[
+    ("BACKSPACE", 0x08),
+    ("TAB", 0x09),
+    ("ENTER", 0x0D),
+    ("SHIFT", 0x10),
+    ("CONTROL", 0x11),
+    ("ALT", 0x12),
+    ("ESCAPE", 0x1B),
+    ("SPACE", 0x20),
+    ("LEFT", 0x25),
+    ("UP", 0x26),
+    ("RIGHT", 0x27),
+    ("DOWN", 0x28),
+];
+
+pub fn key_name(key: u8) -> String {
+    match key {
+        b'A'..=b'Z' | b'0'..=b'9' => (key as char).to_string(),
+        _ => match KEY_NAMES.iter().find(|(_, code)| *code == key) {
+            Some((name, _)) => name.to_string(),
+            None => format!("0x{:02X}", key),
+        },
+    }
+}
+
+pub fn parse_key(name: &str) -> Option<u8> {
+    let name = name.to_ascii_uppercase();
+
+    if let Some(code) = name.strip_prefix("0X") {
+        return u8::from_str_radix(code, 16).ok();
+    }
+
+    match name.as_bytes() {
+        [key @ (b'A'..=b'Z' | b'0'..=b'9')] => Some(*key),
+        _ => KEY_NAMES
+            .iter()
+            .find(|(key_name, _)| *key_name == name)
+            .map(|(_, code)| *code),
+    }
+}
+
+// The NES keeps the level counter digits for levels 0 to 29 in a table of
+// binary-coded decimals. Past level 29 it reads on into the table after it,
+// which holds multiples of ten, so levels 30 to 49 show 00, 0A, 14, ... BE in
+// hexadecimal. Later levels read further on still; those bytes aren't
+// reproduced here, the counter just keeps going up by ten.
+const NES_TABLE_LEVELS: usize = 30;
+
+impl LevelDisplay {
+    pub const ALL: [LevelDisplay; 2] = [LevelDisplay::Decimal, LevelDisplay::Nes];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LevelDisplay::Decimal => "decimal",
+            LevelDisplay::Nes => "nes",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<LevelDisplay> {
+        LevelDisplay::ALL
+            .into_iter()
+            .find(|display| display.name().eq_ignore_ascii_case(name))
+    }
+
+    /// The two digits shown for `level`, each from 0 to 15 where 10 to 15
+    /// are drawn as the letters A to F
+    pub fn digits(self, level: usize) -> [usize; 2] {
+        let value = match self {
+            LevelDisplay::Decimal => (level / 10 % 10) * 16 + level % 10,
+            LevelDisplay::Nes => {
+                if level < NES_TABLE_LEVELS {
+                    (level / 10) * 16 + level % 10
+                } else {
+                    ((level - NES_TABLE_LEVELS) * 10) % 256
+                }
+            }
+        };
+
+        [value / 16, value % 16]
+    }
+}
+
+impl ScoreDisplay {
+    pub const ALL: [ScoreDisplay; 2] = [ScoreDisplay::Classic, ScoreDisplay::Modern];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ScoreDisplay::Classic => "classic",
+            ScoreDisplay::Modern => "modern",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ScoreDisplay> {
+        ScoreDisplay::ALL
+            .into_iter()
+            .find(|display| display.name().eq_ignore_ascii_case(name))
+    }
+}
+
+fn parse_frames(value: &str) -> Result<u8, String> {
+    match value.parse() {
+        Ok(frames) if frames > 0 => Ok(frames),
+        _ => Err(format!("\"{}\" isn't a number of frames from 1 to 255", value)),
+    }
+}
+
+// Each player's prefix on their versus bindings
+const PLAYER_PREFIXES: [&str; 2] = ["p1_", "p2_"];
+
+// Rows of garbage, comma separated, one for each entry in an attack table row
+fn parse_rows<const N: usize>(value: &str) -> Result<[usize; N], String> {
+    let error = || format!("\"{}\" isn't a list of {} numbers of rows", value, N);
+
+    let mut rows = [0; N];
+    let mut values = value.split(',');
+    for row in rows.iter_mut() {
+        *row = values
+            .next()
+            .and_then(|rows| rows.trim().parse().ok())
+            .ok_or_else(error)?;
+    }
+
+    match values.next() {
+        Some(_) => Err(error()),
+        None => Ok(rows),
+    }
+}
+
+fn parse_row_count(value: &str) -> Result<usize, String> {
+    value
+        .parse()
+        .map_err(|_| format!("\"{}\" isn't a number of rows", value))
+}
+
+fn parse_keys(value: &str) -> Result<Vec<u8>, String> {
+    value
+        .split(',')
+        .map(|key| parse_key(key.trim()).ok_or_else(|| format!("unknown key \"{}\"", key.trim())))
+        .collect()
+}
+
+fn join_rows(rows: &[usize]) -> String {
+    let rows: Vec<String> = rows.iter().map(|rows| rows.to_string()).collect();
+    rows.join(", ")
+}
+
+impl KeyBindings {
+    pub fn keys(&self, action: Action) -> &[u8] {
+        &self.keys[action as usize]
+    }
+
+    pub fn set(&mut self, action: Action, keys: Vec<u8>) {
+        self.keys[action as usize] = keys;
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        let mut bindings = KeyBindings {
+            keys: Default::default(),
+        };
+
+        bindings.set(Action::MoveLeft, vec![b'A', 0x25]);
+        bindings.set(Action::MoveRight, vec![b'D', 0x27]);
+        bindings.set(Action::SoftDrop, vec![b'S', 0x28]);
+        bindings.set(Action::RotateCounterclockwise, vec![b'Q', b'Z']);
+        bindings.set(Action::RotateClockwise, vec![b'E', b'X']);
+        bindings.set(Action::HardDrop, vec![b'W', 0x20]);
+        bindings.set(Action::Hold, vec![b'C', 0x10]);
+        bindings.set(Action::Pause, vec![0x1B]);
+
+        bindings
+    }
+}
+
+impl KeyBindings {
+    /// The default versus keys: player one on the left of the keyboard and
+    /// player two around the arrows
+    pub fn versus() -> [KeyBindings; 2] {
+        let mut left = KeyBindings {
+            keys: Default::default(),
+        };
+
+        left.set(Action::MoveLeft, vec![b'A']);
+        left.set(Action::MoveRight, vec![b'D']);
+        left.set(Action::SoftDrop, vec![b'S']);
+        left.set(Action::RotateCounterclockwise, vec![b'Q']);
+        left.set(Action::RotateClockwise, vec![b'E']);
+        left.set(Action::HardDrop, vec![b'W']);
+        left.set(Action::Hold, vec![b'F']);
+        left.set(Action::Pause, vec![0x1B]);
+
+        let mut right = KeyBindings {
+            keys: Default::default(),
+        };
+
+        right.set(Action::MoveLeft, vec![0x25]);
+        right.set(Action::MoveRight, vec![0x27]);
+        right.set(Action::SoftDrop, vec![0x28]);
+        right.set(Action::RotateCounterclockwise, vec![b'K']);
+        right.set(Action::RotateClockwise, vec![b'L']);
+        right.set(Action::HardDrop, vec![0x26]);
+        right.set(Action::Hold, vec![b'J']);
+        right.set(Action::Pause, vec![b'P']);
+
+        [left, right]
+    }
+
+    // Writes one `action = keys` line per action, each name after `prefix`
+    fn save(&self, prefix: &str, file: &mut File) -> std::io::Result<()> {
+        for action in Action::ALL {
+            let keys: Vec<String> = self.keys(action).iter().map(|key| key_name(*key)).collect();
+            writeln!(file, "{}{} = {}", prefix, action.name(), keys.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Config {
+    /// Loads the settings at `path`, or the defaults if there is no file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let mut config = Config::default();
+
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(config),
+            Err(error) => return Err(error.into()),
+        };
+
+        for (i, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            config
+                .parse_setting(line)
+                .map_err(|message| ConfigError::Invalid {
+                    line: i + 1,
+                    message,
+                })?;
+        }
+
+        if !config.das.is_valid() {
+            return Err(ConfigError::InvalidDas);
+        }
+
+        Ok(config)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ConfigError> {
+        let mut file = File::create(path)?;
+
+        writeln!(file, "# Key bindings")?;
+        self.bindings.save("", &mut file)?;
+
+        writeln!(file)?;
+        writeln!(file, "# Versus key bindings, player one then player two")?;
+        for (prefix, bindings) in PLAYER_PREFIXES.iter().zip(&self.versus_bindings) {
+            bindings.save(prefix, &mut file)?;
+        }
+
+        writeln!(file)?;
+        writeln!(file, "# Garbage sent in versus for a single to a tetris, T-spin")?;
+        writeln!(file, "# singles to triples, and bonuses added for back-to-back")?;
+        writeln!(file, "# clears and perfect clears")?;
+        writeln!(file, "attack_lines = {}", join_rows(&self.attack.lines))?;
+        writeln!(file, "attack_t_spin = {}", join_rows(&self.attack.t_spin))?;
+        writeln!(file, "attack_back_to_back = {}", self.attack.back_to_back)?;
+        writeln!(file, "attack_perfect_clear = {}", self.attack.perfect_clear)?;
+
+        writeln!(file)?;
+        writeln!(file, "# Delayed auto shift")?;
+        writeln!(file, "das_initial_delay = {}", self.das.initial_delay)?;
+        writeln!(file, "das_repeat_delay = {}", self.das.repeat_delay)?;
+
+        writeln!(file)?;
+        writeln!(file, "# Pieces shown ahead, from 1 to {}", MAX_NEXT_QUEUE_LENGTH)?;
+        writeln!(file, "next_queue_length = {}", self.next_queue_length)?;
+
+        writeln!(file)?;
+        writeln!(
+            file,
+            "# Garbage rows Dig starts with, from 1 to {}",
+            MAX_DIG_ROWS
+        )?;
+        writeln!(file, "dig_rows = {}", self.dig_rows)?;
+
+        let randomizers: Vec<&str> = RandomizerKind::ALL.iter().map(|kind| kind.name()).collect();
+        writeln!(file)?;
+        writeln!(file, "# One of {}", randomizers.join(", "))?;
+        writeln!(file, "randomizer = {}", self.randomizer.name())?;
+
+        writeln!(file)?;
+        writeln!(file, "# decimal, or nes for the NES digits past level 29")?;
+        writeln!(file, "level_display = {}", self.level_display.name())?;
+        writeln!(file, "# classic stops at 999999, modern has a seventh digit")?;
+        writeln!(file, "score_display = {}", self.score_display.name())?;
+
+        Ok(())
+    }
+
+    fn parse_setting(&mut self, line: &str) -> Result<(), String> {
+        let (name, value) = line
+            .split_once('=')
+            .ok_or_else(|| "expected \"name = value\"".to_owned())?;
+        let (name, value) = (name.trim(), value.trim());
+
+        match name {
+            "das_initial_delay" => self.das.initial_delay = parse_frames(value)?,
+            "das_repeat_delay" => self.das.repeat_delay = parse_frames(value)?,
+            "randomizer" => {
+                self.randomizer = RandomizerKind::from_name(value)
+                    .ok_or_else(|| format!("unknown randomizer \"{}\"", value))?
+            }
+            "level_display" => {
+                self.level_display = LevelDisplay::from_name(value)
+                    .ok_or_else(|| format!("unknown level display \"{}\"", value))?
+            }
+            "score_display" => {
+                self.score_display = ScoreDisplay::from_name(value)
+                    .ok_or_else(|| format!("unknown score display \"{}\"", value))?
+            }
+            "attack_lines" => self.attack.lines = parse_rows(value)?,
+            "attack_t_spin" => self.attack.t_spin = parse_rows(value)?,
+            "attack_back_to_back" => self.attack.back_to_back = parse_row_count(value)?,
+            "attack_perfect_clear" => self.attack.perfect_clear = parse_row_count(value)?,
+            "next_queue_length" => {
+                self.next_queue_length = match value.parse() {
+                    Ok(length) if (1..=MAX_NEXT_QUEUE_LENGTH).contains(&length) => length,
+                    _ => {
+                        return Err(format!(
+                            "\"{}\" isn't a queue length from 1 to {}",
+                            value, MAX_NEXT_QUEUE_LENGTH
+                        ))
+                    }
+                }
+            }
+            "dig_rows" => {
+                self.dig_rows = match parse_row_count(value)? {
+                    rows if (1..=MAX_DIG_ROWS).contains(&rows) => rows,
+                    _ => {
+                        return Err(format!(
+                            "\"{}\" isn't a number of rows from 1 to {}",
+                            value, MAX_DIG_ROWS
+                        ))
+                    }
+                }
+            }
+            _ => {
+                // Versus bindings are the player's prefix then the action
+                let (bindings, action) = match PLAYER_PREFIXES
+                    .iter()
+                    .position(|prefix| name.starts_with(prefix))
+                {
+                    Some(player) => (
+                        &mut self.versus_bindings[player],
+                        &name[PLAYER_PREFIXES[player].len()..],
+                    ),
+                    None => (&mut self.bindings, name),
+                };
+
+                match Action::from_name(action) {
+                    Some(action) => bindings.set(action, parse_keys(value)?),
+                    None => return Err(format!("unknown setting \"{}\"", name)),
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            bindings: KeyBindings::default(),
+            versus_bindings: KeyBindings::versus(),
+            attack: AttackTable::default(),
+            das: DasSettings::default(),
+            next_queue_length: 1,
+            dig_rows: DEFAULT_DIG_ROWS,
+            randomizer: RandomizerKind::SevenBag,
+            level_display: LevelDisplay::Decimal,
+            score_display: ScoreDisplay::Classic,
+        }
+    }
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Io(error) => error.fmt(f),
+            ConfigError::Invalid { line, message } => write!(f, "line {}: {}", line, message),
+            ConfigError::InvalidDas => write!(
+                f,
+                "das_repeat_delay can't be longer than das_initial_delay"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<std::io::Error> for ConfigError {
+    fn from(error: std::io::Error) -> Self {
+        ConfigError::Io(error)
+    }
+}
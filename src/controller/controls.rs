@@ -0,0 +1,101 @@
+use super::{
+    board_camera,
+    input::{KeyPress, KEY_ENTER},
+    menu::Choices,
+    GameState,
+};
+use crate::{
+    config::KeyBindings,
+    model::Action,
+    view::{ControlsUI, Textures},
+};
+use colosseum::{Camera, Input, StateTrackingInput, Window};
+
+pub enum NextState {
+    Done,
+}
+
+/// Asks for a new key for each action in turn
+pub struct Controls {
+    camera: Camera,
+    choices: Choices,
+
+    action: usize,
+    held: Box<[bool]>,
+    skip_key: KeyPress,
+
+    ui: ControlsUI,
+}
+
+// Enter skips an action, so it can't be bound
+const SKIP_KEY: u8 = KEY_ENTER;
+
+impl Controls {
+    /// Rebinds every action, then goes back to the menu with `choices`
+    pub fn new(
+        choices: Choices,
+        bindings: &KeyBindings,
+        textures: &Textures,
+        window: &mut Window<StateTrackingInput>,
+    ) -> GameState {
+        GameState::Controls(Controls {
+            camera: board_camera(window),
+            choices,
+            action: 0,
+            held: (0..=u8::MAX)
+                .map(|key| window.input().get_key(key))
+                .collect(),
+            skip_key: KeyPress::new(SKIP_KEY, window),
+            ui: ControlsUI::new(bindings, textures),
+        })
+    }
+
+    pub fn update(
+        &mut self,
+        bindings: &mut KeyBindings,
+        textures: &Textures,
+        window: &mut Window<StateTrackingInput>,
+    ) -> Option<NextState> {
+        let skip = self.skip_key.pressed(window);
+
+        let mut pressed = None;
+        for key in 0..=u8::MAX {
+            let down = window.input().get_key(key);
+            if down && !self.held[key as usize] && key != SKIP_KEY && pressed.is_none() {
+                pressed = Some(key);
+            }
+            self.held[key as usize] = down;
+        }
+
+        self.ui.update();
+
+        match pressed {
+            Some(key) => {
+                bindings.set(Action::ALL[self.action], vec![key]);
+                self.ui.set_row(self.action, bindings, textures);
+            }
+            None => {
+                if !skip {
+                    return None;
+                }
+            }
+        }
+
+        self.action += 1;
+        if self.action == Action::ALL.len() {
+            Some(NextState::Done)
+        } else {
+            self.ui.select(self.action);
+            None
+        }
+    }
+
+    pub fn choices(&self) -> Choices {
+        self.choices
+    }
+
+    pub fn render<I: Input>(&mut self, window: &mut Window<I>) {
+        self.camera.set_active(window);
+        self.ui.render(window);
+    }
+}
@@ -0,0 +1,78 @@
+use super::{
+    input::{KeyPress, KEY_ENTER},
+    menu::Choices,
+    playing::Playing,
+    GameState,
+};
+use crate::{
+    config::Config,
+    high_scores::HighScore,
+    view::{GameOverUI, Textures},
+};
+use colosseum::{Input, StateTrackingInput, Window};
+
+pub enum NextState {
+    Restart,
+}
+
+pub struct GameOver {
+    playing: Playing,
+    ui: GameOverUI,
+    restart_key: KeyPress,
+}
+
+impl GameOver {
+    pub fn new(
+        playing: Playing,
+        config: &Config,
+        textures: &Textures,
+        window: &mut Window<StateTrackingInput>,
+    ) -> GameState {
+        let ui = GameOverUI::new(
+            playing.game(),
+            config.level_display,
+            config.score_display,
+            textures,
+            window,
+        );
+
+        GameState::GameOver(GameOver {
+            playing,
+            ui,
+            restart_key: KeyPress::new(KEY_ENTER, window),
+        })
+    }
+
+    pub fn update(&mut self, window: &mut Window<StateTrackingInput>) -> Option<NextState> {
+        if self.restart_key.pressed(window) {
+            Some(NextState::Restart)
+        } else {
+            None
+        }
+    }
+
+    pub fn choices(&self) -> Choices {
+        self.playing.choices()
+    }
+
+    pub fn starting_level(&self) -> usize {
+        self.playing.starting_level()
+    }
+
+    pub fn is_replay(&self) -> bool {
+        self.playing.is_replay()
+    }
+
+    /// An unnamed table entry for this game, if its mode keeps high scores
+    pub fn high_score(&self) -> Option<HighScore> {
+        let game = self.playing.game();
+        game.mode()
+            .keeps_high_scores()
+            .then(|| HighScore::new(game.score(), game.total_lines(), self.starting_level()))
+    }
+
+    pub fn render<I: Input>(&mut self, window: &mut Window<I>) {
+        self.playing.render_paused(window);
+        self.ui.render(window);
+    }
+}
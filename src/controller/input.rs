@@ -0,0 +1,71 @@
+use crate::{
+    config::KeyBindings,
+    model::{Action, InputFrame},
+};
+use colosseum::{StateTrackingInput, Window};
+
+pub const KEY_BACKSPACE: u8 = 0x08;
+pub const KEY_ENTER: u8 = 0x0D;
+pub const KEY_SPACE: u8 = 0x20;
+pub const KEY_LEFT: u8 = 0x25;
+pub const KEY_UP: u8 = 0x26;
+pub const KEY_RIGHT: u8 = 0x27;
+pub const KEY_DOWN: u8 = 0x28;
+
+/// Detects the frame a key goes down, so holding it doesn't repeat
+pub struct KeyPress {
+    keys: Vec<u8>,
+    held: bool,
+}
+
+impl KeyPress {
+    /// A key already held when this is created counts as pressed only after
+    /// it has been released
+    pub fn new(key: u8, window: &Window<StateTrackingInput>) -> Self {
+        KeyPress::any(&[key], window)
+    }
+
+    /// Detects any of `keys` going down
+    pub fn any(keys: &[u8], window: &Window<StateTrackingInput>) -> Self {
+        let mut key_press = KeyPress {
+            keys: keys.to_vec(),
+            held: false,
+        };
+        key_press.held = key_press.down(window);
+        key_press
+    }
+
+    pub fn pressed(&mut self, window: &Window<StateTrackingInput>) -> bool {
+        let down = self.down(window);
+        let pressed = down && !self.held;
+        self.held = down;
+        pressed
+    }
+
+    fn down(&self, window: &Window<StateTrackingInput>) -> bool {
+        self.keys.iter().any(|key| window.input().get_key(*key))
+    }
+}
+
+pub fn action_held(
+    action: Action,
+    bindings: &KeyBindings,
+    window: &Window<StateTrackingInput>,
+) -> bool {
+    bindings
+        .keys(action)
+        .iter()
+        .any(|key| window.input().get_key(*key))
+}
+
+/// Reads the actions held this frame from the keyboard
+pub fn read_input_frame(bindings: &KeyBindings, window: &Window<StateTrackingInput>) -> InputFrame {
+    let mut frame = InputFrame::default();
+    for action in Action::ALL {
+        if action != Action::Pause && action_held(action, bindings, window) {
+            frame.hold(action);
+        }
+    }
+
+    frame
+}
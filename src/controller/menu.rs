@@ -0,0 +1,233 @@
+use super::{
+    board_camera,
+    input::{
+        action_held, KeyPress, KEY_BACKSPACE, KEY_DOWN, KEY_ENTER, KEY_LEFT, KEY_RIGHT, KEY_UP,
+    },
+    GameState,
+};
+use crate::{
+    config::KeyBindings,
+    model::{Action, GameMode, Ruleset},
+    view::{MenuItem, MenuUI, Textures},
+};
+use colosseum::{Camera, Input, StateTrackingInput, Window};
+
+pub enum NextState {
+    Start,
+    Replay,
+    Controls,
+    Versus,
+}
+
+/// The settings picked on the menu, kept so they come back after a game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Choices {
+    pub mode: GameMode,
+    pub ruleset: Ruleset,
+    pub level: usize,
+    /// `None` picks a new seed for every game
+    pub seed: Option<u32>,
+}
+
+pub struct Menu {
+    camera: Camera,
+
+    choices: Choices,
+    selected: MenuItem,
+
+    up_key: KeyPress,
+    down_key: KeyPress,
+    left_key: KeyPress,
+    right_key: KeyPress,
+    digit_keys: Box<[KeyPress]>,
+    backspace_key: KeyPress,
+    start_key: KeyPress,
+    replay_key: KeyPress,
+    controls_key: KeyPress,
+    versus_key: KeyPress,
+
+    ui: MenuUI,
+}
+
+/// The highest level that can be picked, before any bonus levels
+pub const MAX_LEVEL: usize = 19;
+
+// Holding rotate clockwise, the NES "A" button, when starting adds 10 levels
+const BONUS_LEVEL_ACTION: Action = Action::RotateClockwise;
+const BONUS_LEVELS: usize = 10;
+
+const REPLAY_KEY: u8 = b'R';
+const CONTROLS_KEY: u8 = b'K';
+const VERSUS_KEY: u8 = b'V';
+
+impl Menu {
+    pub fn new(
+        choices: Choices,
+        textures: &Textures,
+        window: &mut Window<StateTrackingInput>,
+    ) -> GameState {
+        let choices = Choices {
+            level: choices.level.min(MAX_LEVEL),
+            ..choices
+        };
+
+        GameState::Menu(Menu {
+            camera: board_camera(window),
+            choices,
+            selected: MenuItem::Mode,
+            up_key: KeyPress::any(&[b'W', KEY_UP], window),
+            down_key: KeyPress::any(&[b'S', KEY_DOWN], window),
+            left_key: KeyPress::any(&[b'A', KEY_LEFT], window),
+            right_key: KeyPress::any(&[b'D', KEY_RIGHT], window),
+            digit_keys: (b'0'..=b'9')
+                .map(|digit| KeyPress::new(digit, window))
+                .collect(),
+            backspace_key: KeyPress::new(KEY_BACKSPACE, window),
+            start_key: KeyPress::new(KEY_ENTER, window),
+            replay_key: KeyPress::new(REPLAY_KEY, window),
+            controls_key: KeyPress::new(CONTROLS_KEY, window),
+            versus_key: KeyPress::new(VERSUS_KEY, window),
+            ui: MenuUI::new(
+                choices.mode,
+                choices.ruleset,
+                choices.level,
+                choices.seed,
+                textures,
+                window,
+            ),
+        })
+    }
+
+    pub fn update(
+        &mut self,
+        textures: &Textures,
+        window: &mut Window<StateTrackingInput>,
+    ) -> Option<NextState> {
+        let up = self.up_key.pressed(window);
+        let down = self.down_key.pressed(window);
+        let left = self.left_key.pressed(window);
+        let right = self.right_key.pressed(window);
+
+        if up {
+            self.selected = match self.selected {
+                MenuItem::Mode => MenuItem::Seed,
+                MenuItem::Level => MenuItem::Mode,
+                MenuItem::Ruleset => MenuItem::Level,
+                MenuItem::Seed => MenuItem::Ruleset,
+            };
+        }
+
+        if down {
+            self.selected = match self.selected {
+                MenuItem::Mode => MenuItem::Level,
+                MenuItem::Level => MenuItem::Ruleset,
+                MenuItem::Ruleset => MenuItem::Seed,
+                MenuItem::Seed => MenuItem::Mode,
+            };
+        }
+
+        let choices = &mut self.choices;
+        match self.selected {
+            MenuItem::Mode => {
+                let count = GameMode::ALL.len();
+                if left {
+                    choices.mode = GameMode::ALL[(choices.mode as usize + count - 1) % count];
+                }
+
+                if right {
+                    choices.mode = GameMode::ALL[(choices.mode as usize + 1) % count];
+                }
+            }
+            MenuItem::Level => {
+                if left && choices.level > 0 {
+                    choices.level -= 1;
+                }
+
+                if right && choices.level < MAX_LEVEL {
+                    choices.level += 1;
+                }
+            }
+            MenuItem::Ruleset => {
+                if left || right {
+                    choices.ruleset = match choices.ruleset {
+                        Ruleset::Classic => Ruleset::Modern,
+                        Ruleset::Modern => Ruleset::Classic,
+                    };
+                }
+            }
+            MenuItem::Seed => {
+                // The seed is typed in, and clearing it goes back to random
+                for (digit, key) in self.digit_keys.iter_mut().enumerate() {
+                    if key.pressed(window) {
+                        let seed = choices.seed.unwrap_or(0) as u64 * 10 + digit as u64;
+                        if seed <= u32::MAX as u64 {
+                            choices.seed = Some(seed as u32);
+                        }
+                    }
+                }
+
+                if self.backspace_key.pressed(window) {
+                    choices.seed = choices.seed.map(|seed| seed / 10).filter(|seed| *seed > 0);
+                }
+            }
+        }
+
+        self.ui.update(
+            self.choices.mode,
+            self.choices.ruleset,
+            self.choices.level,
+            self.choices.seed,
+            self.selected,
+            textures,
+        );
+
+        let start = self.start_key.pressed(window);
+        let replay = self.replay_key.pressed(window);
+        let controls = self.controls_key.pressed(window);
+        let versus = self.versus_key.pressed(window);
+
+        if start {
+            Some(NextState::Start)
+        } else if replay {
+            Some(NextState::Replay)
+        } else if controls {
+            Some(NextState::Controls)
+        } else if versus {
+            Some(NextState::Versus)
+        } else {
+            None
+        }
+    }
+
+    pub fn choices(&self) -> Choices {
+        self.choices
+    }
+
+    pub fn starting_level(
+        &self,
+        bindings: &KeyBindings,
+        window: &Window<StateTrackingInput>,
+    ) -> usize {
+        if action_held(BONUS_LEVEL_ACTION, bindings, window) {
+            self.choices.level + BONUS_LEVELS
+        } else {
+            self.choices.level
+        }
+    }
+
+    pub fn render<I: Input>(&mut self, window: &mut Window<I>) {
+        self.camera.set_active(window);
+        self.ui.render(window);
+    }
+}
+
+impl Default for Choices {
+    fn default() -> Self {
+        Choices {
+            mode: GameMode::Marathon,
+            ruleset: Ruleset::Classic,
+            level: 0,
+            seed: None,
+        }
+    }
+}
@@ -1,15 +1,106 @@
-use self::playing::{NextState as PlayingNextState, Playing};
-use crate::view::Textures;
-use colosseum::{Input, StateTrackingInput};
+pub use self::menu::{Choices, MAX_LEVEL};
 
+use self::{
+    controls::{Controls, NextState as ControlsNextState},
+    game_over::{GameOver, NextState as GameOverNextState},
+    menu::{Menu, NextState as MenuNextState},
+    name_entry::{NameEntry, NextState as NameEntryNextState},
+    net_versus::{NetVersus, NextState as NetVersusNextState},
+    paused::{NextState as PausedNextState, Paused},
+    playing::{NextState as PlayingNextState, Playing},
+    versus::{NextState as VersusNextState, Versus},
+    versus_over::{NextState as VersusOverNextState, VersusOver},
+};
+#[cfg(feature = "audio")]
+use crate::audio::DeviceAudio;
+use crate::{
+    audio::{Audio, NullAudio},
+    broadcast::Broadcast,
+    cli::{BroadcastTarget, Netplay, Options},
+    config::Config,
+    high_scores::HighScores,
+    model::{PieceGenerator, RandomizerKind, BOARD_HEIGHT, BOARD_WIDTH},
+    netplay::{Connection, Settings},
+    replay::Replay,
+    view::Textures,
+};
+use colosseum::{Camera, Input, Projection, StateTrackingInput, Vector3, Window};
+use std::{
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+mod controls;
+mod game_over;
+mod input;
+mod menu;
+mod name_entry;
+mod net_versus;
+mod paused;
 mod playing;
+mod versus;
+mod versus_over;
 
 pub enum GameState {
+    Menu(Menu),
+    Controls(Controls),
     Playing(Playing),
+    Paused(Paused),
+    GameOver(GameOver),
+    NameEntry(NameEntry),
+    Versus(Versus),
+    VersusOver(VersusOver),
+    NetVersus(NetVersus),
 }
 
 pub struct Game {
-    current_state: GameState,
+    // Only empty while a state is being replaced in `fixed_update`
+    current_state: Option<GameState>,
+    resources: Resources,
+}
+
+/// Everything that outlives a single state
+pub struct Resources {
+    textures: Textures,
+    high_scores: HighScores,
+    config: Config,
+    config_path: PathBuf,
+    record_path: PathBuf,
+    randomizer: Option<RandomizerKind>,
+    audio: Audio,
+    broadcast: Broadcast,
+}
+
+const HIGH_SCORES_PATH: &str = "./highscores.txt";
+#[cfg(feature = "audio")]
+const AUDIO_PATH: &str = "./audio";
+
+/// What is read and set up before the window opens
+struct Startup {
+    options: Options,
+    config: Config,
+    connection: Option<Connection>,
+}
+
+// `colosseum::Game::new` only gets the window, so the startup state waits here
+static STARTUP: Mutex<Option<Startup>> = Mutex::new(None);
+
+/// Opens the game window and runs until it is closed
+pub fn run(options: Options) {
+    let config = load_config(&options.config_path);
+    // Waiting for the other player would leave the window hanging, so it
+    // happens first
+    let connection = options
+        .netplay
+        .as_ref()
+        .map(|netplay| open_connection(netplay, &options, &config));
+
+    *STARTUP.lock().unwrap() = Some(Startup {
+        options,
+        config,
+        connection,
+    });
+    colosseum::App::<Game>::new()
 }
 
 impl colosseum::Game for Game {
@@ -19,21 +110,81 @@ impl colosseum::Game for Game {
     const INITIAL_FIXED_UPDATE_DELTA_TIME: Option<f32> = Some(1.0 / 60.0);
 
     fn new(window: &mut colosseum::Window<Self::Input>) -> Self {
-        let textures = Textures::load(window);
+        let Startup {
+            options,
+            config,
+            connection,
+        } = STARTUP
+            .lock()
+            .unwrap()
+            .take()
+            .expect("run sets up the startup state");
+
+        // colosseum opens the window at a size of its own choosing and has no
+        // way to change it, so a different size can only be reported
+        if let Some((width, height)) = options.window_size {
+            let size = (window.width() as u32, window.height() as u32);
+            if size != (width, height) {
+                eprintln!(
+                    "Unable to size the window to {}x{}, it is {}x{}",
+                    width, height, size.0, size.1
+                );
+            }
+        }
+
+        let mut resources = Resources {
+            textures: Textures::load(window),
+            high_scores: load_high_scores(),
+            config,
+            config_path: options.config_path,
+            record_path: options.record_path,
+            randomizer: options.randomizer,
+            audio: open_audio(),
+            broadcast: open_broadcast(options.broadcast.as_ref()),
+        };
+
+        let current_state = match (connection, options.replay) {
+            (Some(connection), _) => {
+                resources.audio.start_music();
+                NetVersus::new(
+                    connection,
+                    options.choices,
+                    resources.high_scores.top_score(),
+                    &resources.config,
+                    &resources.textures,
+                    window,
+                )
+            }
+            (None, Some(replay)) => {
+                resources.audio.start_music();
+                Playing::replay(
+                    replay,
+                    resources.high_scores.top_score(),
+                    &resources.config,
+                    &resources.textures,
+                    window,
+                )
+            }
+            (None, None) => Menu::new(options.choices, &resources.textures, window),
+        };
 
         Game {
-            current_state: playing::Playing::new(0, &textures, window),
+            current_state: Some(current_state),
+            resources,
         }
     }
 
     fn update(&mut self, _: f32, _: &mut colosseum::Window<Self::Input>) {}
 
     fn fixed_update(&mut self, window: &mut colosseum::Window<Self::Input>) {
-        self.current_state.update(window);
+        let current_state = self.current_state.take().unwrap();
+        self.current_state = Some(current_state.update(&mut self.resources, window));
     }
 
     fn render(&mut self, window: &mut colosseum::Window<Self::Input>) {
-        self.current_state.render(window)
+        if let Some(current_state) = self.current_state.as_mut() {
+            current_state.render(window)
+        }
     }
 
     fn clear_color(&self) -> [f32; 4] {
@@ -42,21 +193,362 @@ impl colosseum::Game for Game {
 }
 
 impl GameState {
-    pub fn update(&mut self, window: &mut colosseum::Window<StateTrackingInput>) {
+    pub fn update(
+        self,
+        resources: &mut Resources,
+        window: &mut colosseum::Window<StateTrackingInput>,
+    ) -> GameState {
+        let Resources {
+            textures,
+            high_scores,
+            config,
+            config_path,
+            record_path,
+            randomizer,
+            audio,
+            broadcast,
+        } = resources;
+
         match self {
-            Self::Playing(playing) => match playing.update(window) {
+            Self::Menu(mut menu) => match menu.update(textures, window) {
                 Some(next_state) => match next_state {
-                    PlayingNextState::GameOver => panic!("Game Over!"),
-                    PlayingNextState::Pause => panic!("Pause!"),
+                    MenuNextState::Start => {
+                        audio.start_music();
+                        Playing::new(
+                            menu.choices(),
+                            menu.starting_level(&config.bindings, window),
+                            high_scores.top_score(),
+                            config,
+                            randomizer.unwrap_or(config.randomizer),
+                            textures,
+                            window,
+                        )
+                    }
+                    MenuNextState::Replay => match Replay::load(&record_path) {
+                        Ok(replay) => {
+                            audio.start_music();
+                            Playing::replay(
+                                replay,
+                                high_scores.top_score(),
+                                config,
+                                textures,
+                                window,
+                            )
+                        }
+                        Err(error) => {
+                            eprintln!(
+                                "Unable to load replay \"{}\": {}",
+                                record_path.display(),
+                                error
+                            );
+                            Self::Menu(menu)
+                        }
+                    },
+                    MenuNextState::Controls => {
+                        Controls::new(menu.choices(), &config.bindings, textures, window)
+                    }
+                    MenuNextState::Versus => {
+                        audio.start_music();
+                        Versus::new(
+                            menu.choices(),
+                            [0, 0],
+                            high_scores.top_score(),
+                            config,
+                            randomizer.unwrap_or(config.randomizer),
+                            textures,
+                            window,
+                        )
+                    }
                 },
-                None => {}
+                None => Self::Menu(menu),
             },
+            Self::Controls(mut controls) => {
+                match controls.update(&mut config.bindings, textures, window) {
+                    Some(next_state) => match next_state {
+                        ControlsNextState::Done => {
+                            if let Err(error) = config.save(&config_path) {
+                                eprintln!(
+                                    "Unable to save settings to \"{}\": {}",
+                                    config_path.display(),
+                                    error
+                                );
+                            }
+
+                            Menu::new(controls.choices(), textures, window)
+                        }
+                    },
+                    None => Self::Controls(controls),
+                }
+            }
+            Self::Playing(mut playing) => {
+                match playing.update(&config.bindings, textures, audio, broadcast, window) {
+                    Some(next_state) => match next_state {
+                        PlayingNextState::GameOver => {
+                            audio.stop_music();
+
+                            if let Some(recording) = playing.recording() {
+                                if let Err(error) = recording.save(&record_path) {
+                                    eprintln!(
+                                        "Unable to save replay to \"{}\": {}",
+                                        record_path.display(),
+                                        error
+                                    );
+                                }
+                            }
+
+                            GameOver::new(playing, config, textures, window)
+                        }
+                        PlayingNextState::Pause => {
+                            audio.set_music_paused(true);
+                            Paused::new(playing, &config.bindings, textures, window)
+                        }
+                    },
+                    None => Self::Playing(playing),
+                }
+            }
+            Self::Paused(mut paused) => match paused.update(window) {
+                Some(next_state) => match next_state {
+                    PausedNextState::Resume => {
+                        audio.set_music_paused(false);
+                        paused.resume(&config.bindings, window)
+                    }
+                },
+                None => Self::Paused(paused),
+            },
+            Self::GameOver(mut game_over) => match game_over.update(window) {
+                Some(next_state) => match next_state {
+                    GameOverNextState::Restart => {
+                        let rank = match game_over.high_score() {
+                            Some(entry)
+                                if !game_over.is_replay() && high_scores.qualifies(entry.score) =>
+                            {
+                                high_scores.insert(entry)
+                            }
+                            _ => None,
+                        };
+
+                        match rank {
+                            Some(rank) => NameEntry::new(
+                                rank,
+                                game_over.choices(),
+                                high_scores,
+                                textures,
+                                window,
+                            ),
+                            None => Menu::new(game_over.choices(), textures, window),
+                        }
+                    }
+                },
+                None => Self::GameOver(game_over),
+            },
+            Self::NameEntry(mut name_entry) => {
+                match name_entry.update(high_scores, textures, window) {
+                    Some(next_state) => match next_state {
+                        NameEntryNextState::Done => {
+                            if let Err(error) = high_scores.save(HIGH_SCORES_PATH) {
+                                eprintln!(
+                                    "Unable to save high scores to \"{}\": {}",
+                                    HIGH_SCORES_PATH, error
+                                );
+                            }
+
+                            Menu::new(name_entry.choices(), textures, window)
+                        }
+                    },
+                    None => Self::NameEntry(name_entry),
+                }
+            }
+            Self::Versus(mut versus) => {
+                match versus.update(config, textures, audio, broadcast, window) {
+                    Some(next_state) => match next_state {
+                        VersusNextState::RoundOver(outcome) => {
+                            audio.stop_music();
+                            VersusOver::new(versus, outcome, config, textures, window)
+                        }
+                    },
+                    None => Self::Versus(versus),
+                }
+            }
+            Self::VersusOver(mut versus_over) => match versus_over.update(window) {
+                Some(next_state) => match next_state {
+                    VersusOverNextState::NextRound => {
+                        audio.start_music();
+                        Versus::new(
+                            versus_over.choices(),
+                            versus_over.wins(),
+                            high_scores.top_score(),
+                            config,
+                            randomizer.unwrap_or(config.randomizer),
+                            textures,
+                            window,
+                        )
+                    }
+                    VersusOverNextState::Quit => Menu::new(versus_over.choices(), textures, window),
+                },
+                None => Self::VersusOver(versus_over),
+            },
+            Self::NetVersus(mut net_versus) => {
+                match net_versus.update(config, textures, audio, broadcast, window) {
+                    Some(next_state) => match next_state {
+                        NetVersusNextState::Leave => {
+                            audio.stop_music();
+                            Menu::new(net_versus.choices(), textures, window)
+                        }
+                    },
+                    None => Self::NetVersus(net_versus),
+                }
+            }
         }
     }
 
     pub fn render<I: Input>(&mut self, window: &mut colosseum::Window<I>) {
         match self {
+            Self::Menu(menu) => menu.render(window),
+            Self::Controls(controls) => controls.render(window),
             Self::Playing(playing) => playing.render(window),
+            Self::Paused(paused) => paused.render(window),
+            Self::GameOver(game_over) => game_over.render(window),
+            Self::NameEntry(name_entry) => name_entry.render(window),
+            Self::Versus(versus) => versus.render(window),
+            Self::VersusOver(versus_over) => versus_over.render(window),
+            Self::NetVersus(net_versus) => net_versus.render(window),
+        }
+    }
+}
+
+/// A camera centred on the board, one world unit per tile
+fn board_camera(window: &mut Window<StateTrackingInput>) -> Camera {
+    let unit_size = window.height() / BOARD_HEIGHT as f32;
+    let width = window.width() / unit_size;
+
+    let mut camera = Camera::new(window);
+    camera.set_projection(Projection::orthographic(width, -0.1, 2.1), window);
+    camera.set_position(Vector3::new(
+        BOARD_WIDTH as f32 / 2.0 - 0.5,
+        BOARD_HEIGHT as f32 / 2.0 + 0.5,
+        0.0,
+    ));
+    camera
+}
+
+// The HUD spans from the hold box to the next queue, and a split screen needs
+// room for two of them side by side
+const VERSUS_WIDTH: f32 = 82.0;
+const HUD_CENTER_X: f32 = 3.5;
+
+/// A camera for one half of a split screen, with player 0 on the left and
+/// player 1 on the right
+fn versus_camera(player: usize, window: &mut Window<StateTrackingInput>) -> Camera {
+    let unit_size = window.height() / BOARD_HEIGHT as f32;
+    let width = (window.width() / unit_size).max(VERSUS_WIDTH);
+    let offset = if player == 0 {
+        width / 4.0
+    } else {
+        -width / 4.0
+    };
+
+    let mut camera = Camera::new(window);
+    camera.set_projection(Projection::orthographic(width, -0.1, 2.1), window);
+    camera.set_position(Vector3::new(
+        HUD_CENTER_X + offset,
+        BOARD_HEIGHT as f32 / 2.0 + 0.5,
+        0.0,
+    ));
+    camera
+}
+
+/// Loads the high score table, moving an unreadable file aside rather than
+/// letting the next save overwrite it
+fn load_high_scores() -> HighScores {
+    match HighScores::load(HIGH_SCORES_PATH) {
+        Ok(high_scores) => high_scores,
+        Err(error) => {
+            eprintln!(
+                "Unable to load high scores from \"{}\": {}",
+                HIGH_SCORES_PATH, error
+            );
+
+            match HighScores::backup(HIGH_SCORES_PATH) {
+                Ok(backup) => eprintln!("The old file was kept as \"{}\"", backup.display()),
+                Err(error) => eprintln!("Unable to back up the old file: {}", error),
+            }
+
+            HighScores::new()
+        }
+    }
+}
+
+/// Opens the sound device, falling back to silence if it or the sounds can't
+/// be opened
+fn open_audio() -> Audio {
+    #[cfg(feature = "audio")]
+    match DeviceAudio::open(Path::new(AUDIO_PATH)) {
+        Ok(device) => return Audio::new(Box::new(device)),
+        Err(error) => eprintln!("Unable to play sound: {}", error),
+    }
+
+    Audio::new(Box::new(NullAudio))
+}
+
+/// Starts the spectator feed, going without if it can't be opened
+fn open_broadcast(target: Option<&BroadcastTarget>) -> Broadcast {
+    let result = match target {
+        Some(BroadcastTarget::File(path)) => Broadcast::to_file(path),
+        Some(BroadcastTarget::Port(port)) => Broadcast::serve(*port),
+        None => return Broadcast::off(),
+    };
+
+    result.unwrap_or_else(|error| {
+        eprintln!("Unable to start the broadcast: {}", error);
+        Broadcast::off()
+    })
+}
+
+/// Hosts or joins a network game, exiting if the other player can't be
+/// reached
+fn open_connection(netplay: &Netplay, options: &Options, config: &Config) -> Connection {
+    let result = match netplay {
+        Netplay::Host(port) => {
+            println!("Waiting for the other player on port {}", port);
+            let settings = Settings {
+                ruleset: options.choices.ruleset,
+                starting_level: options.choices.level,
+                next_queue_length: config.next_queue_length,
+                randomizer: options.randomizer.unwrap_or(config.randomizer),
+                seed: options
+                    .choices
+                    .seed
+                    .unwrap_or_else(PieceGenerator::time_seed),
+                attack: config.attack,
+            };
+            Connection::host(*port, settings, config.das)
+        }
+        Netplay::Connect(address) => {
+            println!("Connecting to {}", address);
+            Connection::connect(address.as_str(), config.das)
+        }
+    };
+
+    match result {
+        Ok(connection) => connection,
+        Err(error) => {
+            eprintln!("Unable to start a network game: {}", error);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Loads the settings, falling back to the defaults if they can't be read
+fn load_config(path: &Path) -> Config {
+    match Config::load(path) {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!(
+                "Unable to load settings from \"{}\": {}",
+                path.display(),
+                error
+            );
+            Config::default()
         }
     }
 }
@@ -0,0 +1,101 @@
+use super::{
+    board_camera,
+    input::{KeyPress, KEY_BACKSPACE, KEY_ENTER, KEY_SPACE},
+    menu::Choices,
+    GameState,
+};
+use crate::{
+    high_scores::{HighScores, MAX_NAME_LENGTH},
+    view::{HighScoresUI, Textures},
+};
+use colosseum::{Camera, Input, StateTrackingInput, Window};
+
+pub enum NextState {
+    Done,
+}
+
+pub struct NameEntry {
+    camera: Camera,
+
+    rank: usize,
+    name: String,
+
+    choices: Choices,
+
+    letter_keys: Box<[KeyPress]>,
+    space_key: KeyPress,
+    backspace_key: KeyPress,
+    done_key: KeyPress,
+
+    ui: HighScoresUI,
+}
+
+impl NameEntry {
+    /// Names the entry at `rank`, which must already be in `high_scores`
+    pub fn new(
+        rank: usize,
+        choices: Choices,
+        high_scores: &HighScores,
+        textures: &Textures,
+        window: &mut Window<StateTrackingInput>,
+    ) -> GameState {
+        GameState::NameEntry(NameEntry {
+            camera: board_camera(window),
+            rank,
+            name: String::with_capacity(MAX_NAME_LENGTH),
+            choices,
+            letter_keys: (b'A'..=b'Z')
+                .map(|letter| KeyPress::new(letter, window))
+                .collect(),
+            space_key: KeyPress::new(KEY_SPACE, window),
+            backspace_key: KeyPress::new(KEY_BACKSPACE, window),
+            done_key: KeyPress::new(KEY_ENTER, window),
+            ui: HighScoresUI::new(high_scores, textures),
+        })
+    }
+
+    pub fn update(
+        &mut self,
+        high_scores: &mut HighScores,
+        textures: &Textures,
+        window: &mut Window<StateTrackingInput>,
+    ) -> Option<NextState> {
+        let mut name = self.name.clone();
+
+        for (letter, key) in (b'A'..=b'Z').zip(self.letter_keys.iter_mut()) {
+            if key.pressed(window) && name.len() < MAX_NAME_LENGTH {
+                name.push(letter as char);
+            }
+        }
+
+        if self.space_key.pressed(window) && name.len() < MAX_NAME_LENGTH {
+            name.push(' ');
+        }
+
+        if self.backspace_key.pressed(window) {
+            name.pop();
+        }
+
+        if name != self.name {
+            self.name = name;
+            high_scores.set_name(self.rank, self.name.clone());
+            self.ui
+                .set_row(self.rank, &high_scores.entries()[self.rank], textures);
+        }
+
+        if self.done_key.pressed(window) {
+            Some(NextState::Done)
+        } else {
+            None
+        }
+    }
+
+    pub fn choices(&self) -> Choices {
+        self.choices
+    }
+
+    pub fn render<I: Input>(&mut self, window: &mut Window<I>) {
+        self.camera.set_active(window);
+        self.ui.render(window);
+    }
+}
@@ -0,0 +1,201 @@
+use super::{
+    input::{read_input_frame, KeyPress, KEY_ENTER},
+    menu::{Choices, Menu},
+    versus::highest_board,
+    versus_camera, GameState,
+};
+use crate::{
+    audio::Audio,
+    broadcast::Broadcast,
+    config::Config,
+    model::{Action, Outcome},
+    netplay::{Connection, Lockstep, NetError},
+    view::{PlayingUI, Textures, VersusOverUI},
+};
+use colosseum::{Camera, Input, StateTrackingInput, Window};
+
+pub enum NextState {
+    Leave,
+}
+
+/// Versus against a player on another machine, with this player always on
+/// the left
+pub struct NetVersus {
+    lockstep: Lockstep,
+    choices: Choices,
+    top_score: usize,
+    wins: [usize; 2],
+    cameras: [Camera; 2],
+
+    // There is no pausing the other player, so pause leaves instead
+    leave_key: KeyPress,
+    rematch_key: KeyPress,
+
+    uis: [PlayingUI; 2],
+    // Shown from the end of a round until the next one starts
+    results: Option<[VersusOverUI; 2]>,
+}
+
+impl NetVersus {
+    /// Starts playing over `connection`, going back to the menu with
+    /// `choices` afterwards
+    pub fn new(
+        connection: Connection,
+        choices: Choices,
+        top_score: usize,
+        config: &Config,
+        textures: &Textures,
+        window: &mut Window<StateTrackingInput>,
+    ) -> GameState {
+        let local = connection.player();
+        let cameras = if local == 0 {
+            [versus_camera(0, window), versus_camera(1, window)]
+        } else {
+            [versus_camera(1, window), versus_camera(0, window)]
+        };
+
+        let lockstep = match Lockstep::new(connection) {
+            Ok(lockstep) => lockstep,
+            Err(error) => {
+                eprintln!("Unable to start a network game: {}", error);
+                return Menu::new(choices, textures, window);
+            }
+        };
+
+        let uis = new_uis(&lockstep, top_score, config, textures, window);
+
+        GameState::NetVersus(NetVersus {
+            lockstep,
+            choices,
+            top_score,
+            wins: [0; 2],
+            cameras,
+            leave_key: KeyPress::any(config.bindings.keys(Action::Pause), window),
+            rematch_key: KeyPress::new(KEY_ENTER, window),
+            uis,
+            results: None,
+        })
+    }
+
+    /// The menu choices to go back to afterwards
+    pub fn choices(&self) -> Choices {
+        self.choices
+    }
+
+    pub fn update(
+        &mut self,
+        config: &Config,
+        textures: &Textures,
+        audio: &mut Audio,
+        broadcast: &mut Broadcast,
+        window: &mut Window<StateTrackingInput>,
+    ) -> Option<NextState> {
+        if self.leave_key.pressed(window) {
+            self.lockstep.quit();
+            return Some(NextState::Leave);
+        }
+
+        if self.results.is_some() {
+            if self.rematch_key.pressed(window) {
+                if let Err(error) = self.lockstep.request_rematch() {
+                    return leave(error);
+                }
+            }
+
+            match self.lockstep.poll_rematch() {
+                Ok(true) => {
+                    self.results = None;
+                    self.uis = new_uis(&self.lockstep, self.top_score, config, textures, window);
+                    audio.start_music();
+                }
+                Ok(false) => {}
+                Err(error) => return leave(error),
+            }
+
+            return None;
+        }
+
+        let input = read_input_frame(&config.bindings, window);
+        let frame = self.lockstep.frame();
+        let outcome = match self.lockstep.update(input) {
+            Ok(outcome) => outcome,
+            Err(error) => return leave(error),
+        };
+
+        // Nothing happens on the frames spent waiting for the other player
+        if self.lockstep.frame() == frame {
+            return None;
+        }
+
+        for player in 0..2 {
+            let events = self.lockstep.take_events(player);
+            let versus = self.lockstep.versus();
+            let simulation = versus.player(player);
+            let game = simulation.game();
+            for event in &events {
+                self.uis[player].handle(event, game, textures);
+                audio.handle(event, highest_board(versus));
+            }
+            self.uis[player].update(game);
+
+            if simulation.frame() == 1 {
+                broadcast.start(player, game);
+            }
+            broadcast.publish(player, game, simulation.frame(), &events, outcome.is_some());
+        }
+
+        if let Some(outcome) = outcome {
+            if let Outcome::Won(winner) = outcome {
+                self.wins[winner] += 1;
+            }
+
+            audio.stop_music();
+            self.results = Some([
+                VersusOverUI::new(0, outcome, self.wins[0], textures),
+                VersusOverUI::new(1, outcome, self.wins[1], textures),
+            ]);
+        }
+
+        None
+    }
+
+    pub fn render<I: Input>(&mut self, window: &mut Window<I>) {
+        for (player, (camera, ui)) in self.cameras.iter_mut().zip(self.uis.iter_mut()).enumerate() {
+            camera.set_active(window);
+            match &mut self.results {
+                Some(results) => {
+                    ui.render_hud(window);
+                    results[player].render(window);
+                }
+                None => ui.render(window),
+            }
+        }
+    }
+}
+
+fn new_uis(
+    lockstep: &Lockstep,
+    top_score: usize,
+    config: &Config,
+    textures: &Textures,
+    window: &mut Window<StateTrackingInput>,
+) -> [PlayingUI; 2] {
+    let mut new_ui = |player: usize| {
+        PlayingUI::new(
+            lockstep.versus().player(player).game(),
+            top_score,
+            config.level_display,
+            config.score_display,
+            false,
+            textures,
+            window,
+        )
+    };
+
+    [new_ui(0), new_ui(1)]
+}
+
+fn leave(error: NetError) -> Option<NextState> {
+    eprintln!("The network game ended: {}", error);
+    Some(NextState::Leave)
+}
@@ -0,0 +1,53 @@
+use super::{input::KeyPress, playing::Playing, GameState};
+use crate::{
+    config::KeyBindings,
+    model::Action,
+    view::{PausedUI, Textures},
+};
+use colosseum::{Input, StateTrackingInput, Window};
+
+pub enum NextState {
+    Resume,
+}
+
+pub struct Paused {
+    playing: Playing,
+    ui: PausedUI,
+    resume_key: KeyPress,
+}
+
+impl Paused {
+    pub fn new(
+        playing: Playing,
+        bindings: &KeyBindings,
+        textures: &Textures,
+        window: &mut Window<StateTrackingInput>,
+    ) -> GameState {
+        GameState::Paused(Paused {
+            playing,
+            ui: PausedUI::new(textures),
+            resume_key: KeyPress::any(bindings.keys(Action::Pause), window),
+        })
+    }
+
+    pub fn update(&mut self, window: &mut Window<StateTrackingInput>) -> Option<NextState> {
+        if self.resume_key.pressed(window) {
+            Some(NextState::Resume)
+        } else {
+            None
+        }
+    }
+
+    pub fn resume(
+        self,
+        bindings: &KeyBindings,
+        window: &mut Window<StateTrackingInput>,
+    ) -> GameState {
+        self.playing.resume(bindings, window)
+    }
+
+    pub fn render<I: Input>(&mut self, window: &mut Window<I>) {
+        self.playing.render_paused(window);
+        self.ui.render(window);
+    }
+}
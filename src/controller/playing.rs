@@ -1,200 +1,243 @@
-use super::GameState;
+use super::{
+    board_camera,
+    input::{read_input_frame, KeyPress},
+    menu::Choices,
+    GameState,
+};
 use crate::{
-    model::{Game, ARE, BOARD_HEIGHT, BOARD_WIDTH},
+    audio::Audio,
+    broadcast::Broadcast,
+    config::{Config, KeyBindings},
+    model::{Action, Game, PieceGenerator, RandomizerKind, Simulation},
+    replay::Replay,
     view::{PlayingUI, Textures},
 };
-use colosseum::{Camera, Input, Projection, StateTrackingInput, Vector3, Window};
-
-#[derive(PartialEq, Eq)]
-enum DASKey {
-    Left,
-    Right,
-    Down,
-    RotateLeft,
-    RotateRight,
-}
-
-enum DAS {
-    None,
-    Active(DASKey, u8),
-}
+use colosseum::{Camera, Input, StateTrackingInput, Window};
 
 pub enum NextState {
     GameOver,
     Pause,
 }
 
+enum Source {
+    /// Input comes from the keyboard and is recorded
+    Live(Replay),
+    /// Input comes from a recording
+    Playback(Replay),
+}
+
 pub struct Playing {
-    game: Game,
+    simulation: Simulation,
+    source: Source,
+    choices: Choices,
     camera: Camera,
 
-    drop_counter: u8,
-    das: DAS,
-
-    are: ARE,
-
-    frame_counter: usize,
+    pause_key: KeyPress,
 
     ui: PlayingUI,
 }
 
-const DAS_INITIAL_DELAY: u8 = 16;
-const DAS_REPEAT_DELAY: u8 = 6;
-
 impl Playing {
+    /// Starts a game from the menu choices, at `starting_level` in case
+    /// bonus levels were added
     pub fn new(
+        choices: Choices,
         starting_level: usize,
+        top_score: usize,
+        config: &Config,
+        randomizer: RandomizerKind,
         textures: &Textures,
         window: &mut Window<StateTrackingInput>,
     ) -> GameState {
-        let unit_size = window.height() / BOARD_HEIGHT as f32;
-        let width = window.width() / unit_size;
+        let seed = choices.seed.unwrap_or_else(PieceGenerator::time_seed);
+        let piece_generator = PieceGenerator::new(seed, randomizer, config.next_queue_length);
+        let recording = Replay::new(
+            choices.mode,
+            choices.ruleset,
+            starting_level,
+            config.dig_rows,
+            config.next_queue_length,
+            randomizer,
+            seed,
+            config.das,
+        );
+        let game = Game::new(
+            choices.mode,
+            choices.ruleset,
+            starting_level,
+            config.dig_rows,
+            piece_generator,
+        );
+
+        Playing::with_source(
+            game,
+            Source::Live(recording),
+            choices,
+            top_score,
+            config,
+            textures,
+            window,
+        )
+    }
 
-        let mut camera = Camera::new(window);
-        camera.set_projection(Projection::orthographic(width, -0.1, 2.1), window);
-        camera.set_position(Vector3::new(
-            BOARD_WIDTH as f32 / 2.0 - 0.5,
-            BOARD_HEIGHT as f32 / 2.0 + 0.5,
-            0.0,
-        ));
+    /// Plays back `replay` in place of the keyboard
+    pub fn replay(
+        replay: Replay,
+        top_score: usize,
+        config: &Config,
+        textures: &Textures,
+        window: &mut Window<StateTrackingInput>,
+    ) -> GameState {
+        let game = replay.new_game();
+        // Going back to the menu after a replay keeps its seed, so the same
+        // pieces can be played again
+        let choices = Choices {
+            mode: replay.mode(),
+            ruleset: replay.ruleset(),
+            level: replay.starting_level(),
+            seed: Some(replay.seed()),
+        };
+
+        Playing::with_source(
+            game,
+            Source::Playback(replay),
+            choices,
+            top_score,
+            config,
+            textures,
+            window,
+        )
+    }
 
-        let game = Game::new(starting_level, textures.tile().clone(), window);
-        let drop_counter = game.drop_time();
-        let ui = PlayingUI::new(&game, 0, textures, window);
+    fn with_source(
+        game: Game,
+        source: Source,
+        choices: Choices,
+        top_score: usize,
+        config: &Config,
+        textures: &Textures,
+        window: &mut Window<StateTrackingInput>,
+    ) -> GameState {
+        let camera = board_camera(window);
+        let ui = PlayingUI::new(
+            &game,
+            top_score,
+            config.level_display,
+            config.score_display,
+            true,
+            textures,
+            window,
+        );
+
+        // Live games are recorded with the DAS settings they are played with
+        let das = match &source {
+            Source::Live(replay) | Source::Playback(replay) => replay.das(),
+        };
 
         GameState::Playing(Playing {
-            game,
+            simulation: Simulation::new(game, das),
+            source,
+            choices,
             camera,
-            drop_counter,
-            frame_counter: 0,
-            das: DAS::None,
-            are: ARE::None,
+            pause_key: KeyPress::any(config.bindings.keys(Action::Pause), window),
             ui,
         })
     }
 
+    pub fn game(&self) -> &Game {
+        self.simulation.game()
+    }
+
+    /// The menu choices to go back to after this game
+    pub fn choices(&self) -> Choices {
+        self.choices
+    }
+
+    pub fn starting_level(&self) -> usize {
+        match &self.source {
+            Source::Live(replay) | Source::Playback(replay) => replay.starting_level(),
+        }
+    }
+
+    pub fn is_replay(&self) -> bool {
+        match self.source {
+            Source::Live(_) => false,
+            Source::Playback(_) => true,
+        }
+    }
+
+    /// The input recorded so far, if this game is being played live
+    pub fn recording(&self) -> Option<&Replay> {
+        match &self.source {
+            Source::Live(recording) => Some(recording),
+            Source::Playback(_) => None,
+        }
+    }
+
+    pub fn resume(
+        mut self,
+        bindings: &KeyBindings,
+        window: &mut Window<StateTrackingInput>,
+    ) -> GameState {
+        self.pause_key = KeyPress::any(bindings.keys(Action::Pause), window);
+        GameState::Playing(self)
+    }
+
     pub fn update(
         &mut self,
-        window: &mut colosseum::Window<colosseum::StateTrackingInput>,
+        bindings: &KeyBindings,
+        textures: &Textures,
+        audio: &mut Audio,
+        broadcast: &mut Broadcast,
+        window: &mut Window<StateTrackingInput>,
     ) -> Option<NextState> {
-        if window.input().get_key(0x1B) {
+        if self.pause_key.pressed(window) {
             return Some(NextState::Pause);
         }
 
-        // Update ARE & frame counter
-        self.frame_counter += 1;
-        match &mut self.are {
-            ARE::ARE(value) => *value -= 1,
-            _ => {}
-        }
-
-        // Read input & update game
-        if window.input().get_key(b'A') {
-            if self.das.add_key_frame(DASKey::Left) {
-                self.game.move_left();
-            }
-        } else if window.input().get_key(b'D') {
-            if self.das.add_key_frame(DASKey::Right) {
-                self.game.move_right();
+        let input = match &mut self.source {
+            Source::Live(recording) => {
+                let input = read_input_frame(bindings, window);
+                recording.push(input);
+                input
             }
-        } else if window.input().get_key(b'Q') {
-            if self.das.add_key_frame(DASKey::RotateLeft) {
-                self.game.rotate_left();
-            }
-        } else if window.input().get_key(b'E') {
-            if self.das.add_key_frame(DASKey::RotateRight) {
-                self.game.rotate_right();
-            }
-        } else if window.input().get_key(b'S') {
-            if self.das.add_key_frame(DASKey::Down) {
-                match self.game.move_down(true) {
-                    Some(are) => match self.are {
-                        ARE::None => self.are = are,
-                        _ => {}
-                    },
-                    None => {}
-                }
-            }
-        } else {
-            self.das = DAS::None;
+            Source::Playback(replay) => replay
+                .frames()
+                .get(self.simulation.frame())
+                .copied()
+                .unwrap_or_default(),
+        };
+
+        let game_over = self.simulation.step(input);
+        let events = self.simulation.take_events();
+        let game = self.simulation.game();
+        for event in &events {
+            self.ui.handle(event, game, textures);
+            audio.handle(event, game.board());
         }
+        self.ui.update(game);
 
-        if match &mut self.are {
-            ARE::ARE(step) => {
-                if *step == 0 {
-                    if self.game.finish_are(window) {
-                        return Some(NextState::GameOver);
-                    }
-                    true
-                } else {
-                    false
-                }
-            }
-            ARE::LineDelay(step, lines_cleared) => {
-                if self.frame_counter % 4 == 0 {
-                    if *step < 5 {
-                        self.game.clear_animation(*step, &lines_cleared);
-                        *step += 1;
-                        false
-                    } else {
-                        self.game.collapse(lines_cleared);
-                        if self.game.finish_are(window) {
-                            return Some(NextState::GameOver);
-                        }
-                        true
-                    }
-                } else {
-                    false
-                }
-            }
-            ARE::None => {
-                if self.drop_counter == 0 {
-                    self.drop_counter = self.game.drop_time();
-                    match self.game.move_down(false) {
-                        Some(are) => self.are = are,
-                        None => {}
-                    }
-                } else {
-                    self.drop_counter -= 1;
-                }
-                false
-            }
-        } {
-            self.are = ARE::None;
+        let frame = self.simulation.frame();
+        if frame == 1 {
+            broadcast.start(0, game);
         }
+        broadcast.publish(0, game, frame, &events, game_over);
 
-        self.ui.update(&self.game);
-        None
+        if game_over {
+            Some(NextState::GameOver)
+        } else {
+            None
+        }
     }
 
     pub fn render<I: Input>(&mut self, window: &mut Window<I>) {
         self.camera.set_active(window);
         self.ui.render(window);
-        self.game.render(window);
     }
-}
 
-impl DAS {
-    pub fn add_key_frame(&mut self, key: DASKey) -> bool {
-        match self {
-            DAS::None => {
-                *self = DAS::Active(key, DAS_INITIAL_DELAY);
-                true
-            }
-            DAS::Active(das_key, count) => {
-                if *das_key != key {
-                    *self = DAS::Active(key, DAS_INITIAL_DELAY);
-                    true
-                } else {
-                    *count -= 1;
-                    if *count == 0 {
-                        *self = DAS::Active(key, DAS_REPEAT_DELAY);
-                        true
-                    } else {
-                        false
-                    }
-                }
-            }
-        }
+    /// Renders the playfield with the board hidden
+    pub fn render_paused<I: Input>(&mut self, window: &mut Window<I>) {
+        self.camera.set_active(window);
+        self.ui.render_hud(window);
     }
 }
@@ -0,0 +1,194 @@
+use super::{
+    input::{read_input_frame, KeyPress},
+    menu::Choices,
+    versus_camera, GameState,
+};
+use crate::{
+    audio::Audio,
+    broadcast::Broadcast,
+    config::Config,
+    model::{
+        Action, Board, Game, GameMode, Outcome, PieceGenerator, RandomizerKind, Simulation,
+        VersusGame,
+    },
+    view::{PausedUI, PlayingUI, Textures},
+};
+use colosseum::{Camera, Input, StateTrackingInput, Window};
+
+pub enum NextState {
+    RoundOver(Outcome),
+}
+
+/// Two players on one keyboard, each with half the screen
+pub struct Versus {
+    game: VersusGame,
+    choices: Choices,
+    wins: [usize; 2],
+    cameras: [Camera; 2],
+
+    // Either player's pause key pauses both games
+    pause_key: KeyPress,
+    paused: bool,
+
+    uis: [PlayingUI; 2],
+    paused_ui: PausedUI,
+}
+
+impl Versus {
+    /// Starts a round dealing both players the same pieces, carrying over
+    /// the tally of rounds won so far
+    pub fn new(
+        choices: Choices,
+        wins: [usize; 2],
+        top_score: usize,
+        config: &Config,
+        randomizer: RandomizerKind,
+        textures: &Textures,
+        window: &mut Window<StateTrackingInput>,
+    ) -> GameState {
+        let seed = choices.seed.unwrap_or_else(PieceGenerator::time_seed);
+        // Versus always levels up, so games can't go on forever
+        let new_game = || {
+            let piece_generator = PieceGenerator::new(seed, randomizer, config.next_queue_length);
+            Game::new(
+                GameMode::Marathon,
+                choices.ruleset,
+                choices.level,
+                0,
+                piece_generator,
+            )
+        };
+        let games = [new_game(), new_game()];
+
+        let new_ui = |game: &Game, window: &mut Window<StateTrackingInput>| {
+            PlayingUI::new(
+                game,
+                top_score,
+                config.level_display,
+                config.score_display,
+                false,
+                textures,
+                window,
+            )
+        };
+        let uis = [new_ui(&games[0], window), new_ui(&games[1], window)];
+
+        let [left, right] = games;
+        let players = [
+            Simulation::new(left, config.das),
+            Simulation::new(right, config.das),
+        ];
+
+        GameState::Versus(Versus {
+            game: VersusGame::new(players, config.attack),
+            choices,
+            wins,
+            cameras: [versus_camera(0, window), versus_camera(1, window)],
+            pause_key: KeyPress::any(&pause_keys(config), window),
+            paused: false,
+            uis,
+            paused_ui: PausedUI::new(textures),
+        })
+    }
+
+    /// The menu choices to go back to after the match
+    pub fn choices(&self) -> Choices {
+        self.choices
+    }
+
+    /// Rounds each player has won, including the last one once it is over
+    pub fn wins(&self) -> [usize; 2] {
+        self.wins
+    }
+
+    pub fn update(
+        &mut self,
+        config: &Config,
+        textures: &Textures,
+        audio: &mut Audio,
+        broadcast: &mut Broadcast,
+        window: &mut Window<StateTrackingInput>,
+    ) -> Option<NextState> {
+        if self.pause_key.pressed(window) {
+            self.paused = !self.paused;
+            audio.set_music_paused(self.paused);
+        }
+
+        if self.paused {
+            return None;
+        }
+
+        let inputs = [
+            read_input_frame(&config.versus_bindings[0], window),
+            read_input_frame(&config.versus_bindings[1], window),
+        ];
+        let outcome = self.game.step(inputs);
+
+        for player in 0..2 {
+            let events = self.game.take_events(player);
+            let simulation = self.game.player(player);
+            let game = simulation.game();
+            for event in &events {
+                self.uis[player].handle(event, game, textures);
+                audio.handle(event, highest_board(&self.game));
+            }
+            self.uis[player].update(game);
+
+            if simulation.frame() == 1 {
+                broadcast.start(player, game);
+            }
+            broadcast.publish(player, game, simulation.frame(), &events, outcome.is_some());
+        }
+
+        if let Some(Outcome::Won(winner)) = outcome {
+            self.wins[winner] += 1;
+        }
+
+        outcome.map(NextState::RoundOver)
+    }
+
+    pub fn render<I: Input>(&mut self, window: &mut Window<I>) {
+        for (camera, ui) in self.cameras.iter_mut().zip(self.uis.iter_mut()) {
+            camera.set_active(window);
+            if self.paused {
+                ui.render_hud(window);
+                self.paused_ui.render(window);
+            } else {
+                ui.render(window);
+            }
+        }
+    }
+
+    /// Renders both playfields with the boards hidden, calling `overlay`
+    /// with each player's camera active
+    pub fn render_hud<I: Input>(
+        &mut self,
+        window: &mut Window<I>,
+        mut overlay: impl FnMut(usize, &mut Window<I>),
+    ) {
+        for (player, (camera, ui)) in self.cameras.iter_mut().zip(self.uis.iter_mut()).enumerate() {
+            camera.set_active(window);
+            ui.render_hud(window);
+            overlay(player, window);
+        }
+    }
+}
+
+/// Every key either player pauses with
+pub fn pause_keys(config: &Config) -> Vec<u8> {
+    config
+        .versus_bindings
+        .iter()
+        .flat_map(|bindings| bindings.keys(Action::Pause))
+        .copied()
+        .collect()
+}
+
+/// The board the music follows, whichever is closer to topping out
+pub fn highest_board(game: &VersusGame) -> &Board {
+    let boards = [game.player(0).game().board(), game.player(1).game().board()];
+    boards
+        .into_iter()
+        .max_by_key(|board| board.stack_height())
+        .unwrap()
+}
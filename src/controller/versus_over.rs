@@ -0,0 +1,73 @@
+use super::{
+    input::{KeyPress, KEY_ENTER},
+    menu::Choices,
+    versus::{pause_keys, Versus},
+    GameState,
+};
+use crate::{
+    config::Config,
+    model::Outcome,
+    view::{Textures, VersusOverUI},
+};
+use colosseum::{Input, StateTrackingInput, Window};
+
+pub enum NextState {
+    NextRound,
+    Quit,
+}
+
+/// Shows who won the round until the players go again or leave
+pub struct VersusOver {
+    versus: Versus,
+    uis: [VersusOverUI; 2],
+    next_round_key: KeyPress,
+    quit_key: KeyPress,
+}
+
+impl VersusOver {
+    pub fn new(
+        versus: Versus,
+        outcome: Outcome,
+        config: &Config,
+        textures: &Textures,
+        window: &mut Window<StateTrackingInput>,
+    ) -> GameState {
+        let wins = versus.wins();
+        let uis = [
+            VersusOverUI::new(0, outcome, wins[0], textures),
+            VersusOverUI::new(1, outcome, wins[1], textures),
+        ];
+
+        GameState::VersusOver(VersusOver {
+            versus,
+            uis,
+            next_round_key: KeyPress::new(KEY_ENTER, window),
+            // Pausing again leaves for the menu
+            quit_key: KeyPress::any(&pause_keys(config), window),
+        })
+    }
+
+    pub fn update(&mut self, window: &mut Window<StateTrackingInput>) -> Option<NextState> {
+        if self.next_round_key.pressed(window) {
+            Some(NextState::NextRound)
+        } else if self.quit_key.pressed(window) {
+            Some(NextState::Quit)
+        } else {
+            None
+        }
+    }
+
+    pub fn choices(&self) -> Choices {
+        self.versus.choices()
+    }
+
+    pub fn wins(&self) -> [usize; 2] {
+        self.versus.wins()
+    }
+
+    pub fn render<I: Input>(&mut self, window: &mut Window<I>) {
+        let uis = &mut self.uis;
+        self.versus
+            .render_hud(window, |player, window| uis[player].render(window));
+    }
+}
@@ -0,0 +1,329 @@
+//! The persistent table of best results
+//!
+//! The table is stored as text: a header line naming the format version,
+//! followed by one tab-separated line per entry, best first.
+
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighScore {
+    pub name: String,
+    pub score: usize,
+    pub lines: usize,
+    pub starting_level: usize,
+    /// Seconds since the Unix epoch
+    pub date: u64,
+}
+
+pub struct HighScores {
+    entries: Vec<HighScore>,
+}
+
+#[derive(Debug)]
+pub enum HighScoreError {
+    Io(std::io::Error),
+    MissingHeader,
+    UnsupportedVersion(String),
+    InvalidEntry(usize),
+}
+
+pub const MAX_HIGH_SCORES: usize = 10;
+pub const MAX_NAME_LENGTH: usize = 6;
+
+const HEADER: &str = "QUADRIS HIGH SCORES";
+const VERSION: &str = "1";
+
+impl HighScore {
+    /// Creates an unnamed entry dated now
+    pub fn new(score: usize, lines: usize, starting_level: usize) -> Self {
+        HighScore {
+            name: String::new(),
+            score,
+            lines,
+            starting_level,
+            date: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or(0),
+        }
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.splitn(5, '\t');
+        let score = fields.next()?.parse().ok()?;
+        let lines = fields.next()?.parse().ok()?;
+        let starting_level = fields.next()?.parse().ok()?;
+        let date = fields.next()?.parse().ok()?;
+        let name = fields.next()?.to_owned();
+
+        Some(HighScore {
+            name,
+            score,
+            lines,
+            starting_level,
+            date,
+        })
+    }
+}
+
+impl HighScores {
+    pub fn new() -> Self {
+        HighScores::default()
+    }
+
+    /// Loads the table at `path`, or an empty table if there is no file yet
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, HighScoreError> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(HighScores::new())
+            }
+            Err(error) => return Err(error.into()),
+        };
+
+        let mut lines = BufReader::new(file).lines();
+        let header = match lines.next() {
+            Some(header) => header?,
+            None => return Err(HighScoreError::MissingHeader),
+        };
+        let version = match header.strip_prefix(HEADER) {
+            Some(version) => version.trim(),
+            None => return Err(HighScoreError::MissingHeader),
+        };
+        if version != VERSION {
+            return Err(HighScoreError::UnsupportedVersion(version.to_owned()));
+        }
+
+        let mut high_scores = HighScores::new();
+        for (i, line) in lines.enumerate() {
+            let line = line?;
+            if line.is_empty() {
+                continue;
+            }
+
+            // Line numbers count from one and include the header
+            let entry = HighScore::parse(&line).ok_or(HighScoreError::InvalidEntry(i + 2))?;
+            high_scores.insert(entry);
+        }
+
+        Ok(high_scores)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), HighScoreError> {
+        let mut file = File::create(path)?;
+        writeln!(file, "{} {}", HEADER, VERSION)?;
+        for entry in &self.entries {
+            writeln!(
+                file,
+                "{}\t{}\t{}\t{}\t{}",
+                entry.score, entry.lines, entry.starting_level, entry.date, entry.name
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Moves an unreadable table out of the way so saving doesn't destroy it
+    pub fn backup<P: AsRef<Path>>(path: P) -> std::io::Result<PathBuf> {
+        let mut backup = path.as_ref().as_os_str().to_owned();
+        backup.push(".bak");
+        let backup = PathBuf::from(backup);
+
+        std::fs::rename(path, &backup)?;
+        Ok(backup)
+    }
+
+    pub fn entries(&self) -> &[HighScore] {
+        &self.entries
+    }
+
+    pub fn top_score(&self) -> usize {
+        self.entries.first().map(|entry| entry.score).unwrap_or(0)
+    }
+
+    pub fn qualifies(&self, score: usize) -> bool {
+        score > 0
+            && (self.entries.len() < MAX_HIGH_SCORES
+                || self.entries.last().map(|entry| score > entry.score).unwrap_or(true))
+    }
+
+    /// Inserts `entry` below any equal scores, returning its rank if it made the table
+    pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+        let rank = self
+            .entries
+            .iter()
+            .position(|existing| entry.score > existing.score)
+            .unwrap_or(self.entries.len());
+        if rank >= MAX_HIGH_SCORES {
+            return None;
+        }
+
+        self.entries.insert(rank, entry);
+        self.entries.truncate(MAX_HIGH_SCORES);
+        Some(rank)
+    }
+
+    pub fn set_name(&mut self, rank: usize, name: String) {
+        self.entries[rank].name = name;
+    }
+}
+
+impl Default for HighScores {
+    fn default() -> Self {
+        HighScores {
+            entries: Vec::with_capacity(MAX_HIGH_SCORES + 1),
+        }
+    }
+}
+
+impl Display for HighScoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HighScoreError::Io(error) => error.fmt(f),
+            HighScoreError::MissingHeader => write!(f, "not a high score file"),
+            HighScoreError::UnsupportedVersion(version) => {
+                write!(f, "unsupported high score file version \"{}\"", version)
+            }
+            HighScoreError::InvalidEntry(line) => write!(f, "invalid entry on line {}", line),
+        }
+    }
+}
+
+impl std::error::Error for HighScoreError {}
+
+impl From<std::io::Error> for HighScoreError {
+    fn from(error: std::io::Error) -> Self {
+        HighScoreError::Io(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A path in the temporary directory that no other test uses
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "quadris-high-scores-{}-{}",
+            std::process::id(),
+            name
+        ));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    fn entry(name: &str, score: usize) -> HighScore {
+        HighScore {
+            name: name.to_owned(),
+            score,
+            lines: score / 100,
+            starting_level: 1,
+            date: 1_600_000_000 + score as u64,
+        }
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let path = temp_path("round-trip");
+        let mut high_scores = HighScores::new();
+        high_scores.insert(entry("ALICE", 5000));
+        high_scores.insert(entry("", 1200));
+        high_scores.insert(entry("BOB", 800));
+        high_scores.save(&path).unwrap();
+
+        let loaded = HighScores::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.entries(), high_scores.entries());
+        assert_eq!(loaded.top_score(), 5000);
+    }
+
+    #[test]
+    fn missing_file_loads_empty() {
+        let high_scores = HighScores::load(temp_path("missing")).unwrap();
+
+        assert!(high_scores.entries().is_empty());
+        assert_eq!(high_scores.top_score(), 0);
+    }
+
+    #[test]
+    fn insert_sorts_and_truncates() {
+        let mut high_scores = HighScores::new();
+        for score in [300, 900, 100, 500, 700, 200, 1000, 400, 600, 800] {
+            assert!(high_scores.insert(entry("", score)).is_some());
+        }
+
+        let scores: Vec<_> = high_scores.entries().iter().map(|e| e.score).collect();
+        assert_eq!(scores, [1000, 900, 800, 700, 600, 500, 400, 300, 200, 100]);
+
+        // A full table only takes scores above its lowest entry
+        assert!(!high_scores.qualifies(100));
+        assert_eq!(high_scores.insert(entry("LOW", 50)), None);
+        assert!(high_scores.qualifies(150));
+        assert_eq!(high_scores.insert(entry("MID", 650)), Some(4));
+        assert_eq!(high_scores.entries().len(), MAX_HIGH_SCORES);
+        assert_eq!(high_scores.entries().last().unwrap().score, 200);
+
+        // Ties go below the existing entry
+        assert_eq!(high_scores.insert(entry("TIE", 900)), Some(2));
+        assert_eq!(high_scores.entries()[1].name, "");
+        assert_eq!(high_scores.entries()[2].name, "TIE");
+    }
+
+    #[test]
+    fn unknown_version_is_backed_up() {
+        let path = temp_path("version");
+        std::fs::write(&path, format!("{} 99\n5000\t50\t1\t0\tALICE\n", HEADER)).unwrap();
+
+        match HighScores::load(&path) {
+            Err(HighScoreError::UnsupportedVersion(version)) => assert_eq!(version, "99"),
+            other => panic!("expected an unsupported version, got {:?}", other.err()),
+        }
+
+        let backup = HighScores::backup(&path).unwrap();
+        assert!(!path.exists());
+        assert_eq!(
+            std::fs::read_to_string(&backup).unwrap(),
+            format!("{} 99\n5000\t50\t1\t0\tALICE\n", HEADER)
+        );
+        std::fs::remove_file(&backup).unwrap();
+    }
+
+    #[test]
+    fn corrupt_line_is_reported() {
+        let path = temp_path("corrupt");
+        std::fs::write(
+            &path,
+            format!(
+                "{} {}\n5000\t50\t1\t0\tALICE\nnot a score\n",
+                HEADER, VERSION
+            ),
+        )
+        .unwrap();
+
+        let result = HighScores::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        match result {
+            Err(HighScoreError::InvalidEntry(line)) => assert_eq!(line, 3),
+            other => panic!("expected an invalid entry, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn missing_header_is_reported() {
+        let path = temp_path("header");
+        std::fs::write(&path, "5000\t50\t1\t0\tALICE\n").unwrap();
+
+        let result = HighScores::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(result, Err(HighScoreError::MissingHeader)));
+    }
+}
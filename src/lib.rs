@@ -0,0 +1,7 @@
+pub mod audio;
+pub mod broadcast;
+pub mod config;
+pub mod high_scores;
+pub mod model;
+pub mod netplay;
+pub mod replay;
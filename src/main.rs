@@ -1,7 +1,19 @@
+mod cli;
 mod controller;
-mod model;
 mod view;
 
+use cli::{Command, USAGE};
+use tetris_clone::{audio, broadcast, config, high_scores, model, netplay, replay};
+
 fn main() {
-    colosseum::App::<controller::Game>::new()
+    match Command::parse(std::env::args().skip(1)) {
+        Ok(Command::Run(options)) => controller::run(options),
+        Ok(Command::Help) => println!("{}", USAGE),
+        Err(error) => {
+            eprintln!("error: {}", error);
+            eprintln!();
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    }
 }
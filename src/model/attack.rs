@@ -0,0 +1,86 @@
+use super::scoring::{Clear, TSpin};
+
+/// How many garbage rows each clear sends to the other player in versus
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttackTable {
+    /// By lines cleared, from a single to a tetris
+    pub lines: [usize; 4],
+    /// T-spins by lines cleared, from a single to a triple. Minis send as
+    /// much as the same clear without a spin.
+    pub t_spin: [usize; 3],
+    /// Added for a back-to-back clear
+    pub back_to_back: usize,
+    /// Added for clearing the whole board
+    pub perfect_clear: usize,
+}
+
+impl AttackTable {
+    /// Garbage rows sent for `clear`
+    pub fn attack(&self, clear: &Clear) -> usize {
+        if clear.lines == 0 {
+            return 0;
+        }
+
+        let mut rows = match clear.t_spin {
+            TSpin::Full => self.t_spin[clear.lines.min(3) - 1],
+            TSpin::None | TSpin::Mini => self.lines[clear.lines.min(4) - 1],
+        };
+
+        if clear.back_to_back {
+            rows += self.back_to_back;
+        }
+
+        if clear.perfect_clear {
+            rows += self.perfect_clear;
+        }
+
+        rows
+    }
+}
+
+impl Default for AttackTable {
+    /// Roughly the guideline table, without combos
+    fn default() -> Self {
+        AttackTable {
+            lines: [0, 1, 2, 4],
+            t_spin: [2, 4, 6],
+            back_to_back: 1,
+            perfect_clear: 10,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clear(lines: usize, t_spin: TSpin) -> Clear {
+        Clear {
+            lines,
+            t_spin,
+            combo: 0,
+            back_to_back: false,
+            perfect_clear: false,
+            points: 0,
+        }
+    }
+
+    #[test]
+    fn attack_by_clear() {
+        let table = AttackTable::default();
+        assert_eq!(table.attack(&clear(0, TSpin::None)), 0);
+        assert_eq!(table.attack(&clear(0, TSpin::Full)), 0);
+        assert_eq!(table.attack(&clear(1, TSpin::None)), 0);
+        assert_eq!(table.attack(&clear(4, TSpin::None)), 4);
+        assert_eq!(table.attack(&clear(2, TSpin::Full)), 4);
+        // Minis send as a plain clear
+        assert_eq!(table.attack(&clear(2, TSpin::Mini)), 1);
+
+        let bonuses = Clear {
+            back_to_back: true,
+            perfect_clear: true,
+            ..clear(4, TSpin::None)
+        };
+        assert_eq!(table.attack(&bonuses), 4 + 1 + 10);
+    }
+}
@@ -1,8 +1,7 @@
-use super::{piece::Piece, tile::Tile};
-use colosseum::{Input, Window};
+use super::{piece::Piece, tile::TileColor};
 
 pub struct Board {
-    tiles: Box<[Option<Tile>]>,
+    tiles: Box<[Option<TileColor>]>,
 }
 
 pub const BOARD_WIDTH: usize = 10;
@@ -35,15 +34,15 @@ impl Board {
         true
     }
 
-    pub fn get(&self, x: isize, y: isize) -> Result<Option<&Tile>, ()> {
+    pub fn get(&self, x: isize, y: isize) -> Result<Option<TileColor>, ()> {
         if x < 0 || x >= BOARD_WIDTH as isize || y < 0 || y >= BOARD_HEIGHT as isize {
             Err(())
         } else {
-            Ok(self.tiles[x as usize + y as usize * BOARD_WIDTH].as_ref())
+            Ok(self.tiles[x as usize + y as usize * BOARD_WIDTH])
         }
     }
 
-    pub fn take(&mut self, x: isize, y: isize) -> Result<Option<Tile>, ()> {
+    pub fn take(&mut self, x: isize, y: isize) -> Result<Option<TileColor>, ()> {
         if x < 0 || x >= BOARD_WIDTH as isize || y < 0 || y >= BOARD_HEIGHT as isize {
             Err(())
         } else {
@@ -51,19 +50,75 @@ impl Board {
         }
     }
 
-    pub fn set(&mut self, x: isize, y: isize, mut tile: Option<Tile>) {
+    pub fn set(&mut self, x: isize, y: isize, tile: Option<TileColor>) {
         assert!(x >= 0 && x < BOARD_WIDTH as isize);
         assert!(y >= 0 && y < BOARD_HEIGHT as isize);
 
-        tile.as_mut().map(|tile| tile.set_position((x, y)));
         self.tiles[x as usize + y as usize * BOARD_WIDTH] = tile;
     }
 
     pub fn finalize(&mut self, piece: Piece) {
-        let tiles = piece.finalize();
-        for (x, y, tile) in tiles {
-            self.set(x, y, Some(tile));
+        let color = piece.color();
+        for i in 0..4 {
+            let (x, y) = piece.get_tile_position(i);
+            self.set(x, y, Some(color));
+        }
+    }
+
+    /// Pushes every row up one and fills the bottom row with garbage, except
+    /// for a hole at column `hole`. Returns false if that pushed any tiles
+    /// off the top.
+    pub fn push_garbage(&mut self, hole: usize) -> bool {
+        let fits = self.is_row_empty(0);
+
+        for y in 0..BOARD_HEIGHT as isize - 1 {
+            for x in 0..BOARD_WIDTH as isize {
+                let tile = self.take(x, y + 1).unwrap();
+                self.set(x, y, tile);
+            }
+        }
+
+        for x in 0..BOARD_WIDTH {
+            let tile = if x == hole {
+                None
+            } else {
+                Some(TileColor::Garbage)
+            };
+            self.set(x as isize, BOARD_HEIGHT as isize - 1, tile);
         }
+
+        fits
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.iter().all(|tile| tile.is_none())
+    }
+
+    /// Whether the board will be empty once `lines` are cleared
+    pub fn is_empty_without(&self, lines: &[isize]) -> bool {
+        (0..BOARD_HEIGHT as isize)
+            .filter(|y| !lines.contains(y))
+            .all(|y| self.is_row_empty(y))
+    }
+
+    pub fn is_row_empty(&self, y: isize) -> bool {
+        (0..BOARD_WIDTH as isize).all(|x| self.get(x, y).unwrap().is_none())
+    }
+
+    /// A hash of every tile, for checking two copies of a game still match
+    pub fn checksum(&self) -> u32 {
+        // FNV-1a, with each tile as its color plus one, or zero if empty
+        self.tiles.iter().fold(0x811C9DC5, |hash, tile| {
+            let byte = tile.map_or(0, |color| color as u32 + 1);
+            (hash ^ byte).wrapping_mul(0x01000193)
+        })
+    }
+
+    /// Rows from the floor up to the highest filled tile
+    pub fn stack_height(&self) -> usize {
+        (0..BOARD_HEIGHT)
+            .find(|y| !self.is_row_empty(*y as isize))
+            .map_or(0, |y| BOARD_HEIGHT - y)
     }
 
     pub fn check(&self) -> Vec<isize> {
@@ -92,15 +147,6 @@ impl Board {
         }
     }
 
-    pub fn render<I: Input>(&mut self, window: &mut Window<I>) {
-        for tile in self.tiles.iter_mut() {
-            match tile.as_mut() {
-                Some(tile) => tile.render(window),
-                None => {}
-            }
-        }
-    }
-
     pub fn clear_animation(&mut self, step: isize, lines: &[isize]) {
         for line in lines {
             self.set(BOARD_WIDTH as isize / 2 - 1 - step, *line, None);
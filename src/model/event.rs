@@ -0,0 +1,38 @@
+use super::{piece::PieceClass, scoring::Clear};
+
+/// Something that happened in a game, published in the order it happened so
+/// views, audio and other listeners don't have to poll the game
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// A piece came into play at the top of the board
+    Spawned(PieceClass),
+    /// The current piece moved by a number of columns and rows, by shifting,
+    /// soft dropping, gravity or a hard drop
+    Moved {
+        x: isize,
+        y: isize,
+    },
+    Rotated {
+        clockwise: bool,
+    },
+    /// The current piece went into the hold slot
+    Held(PieceClass),
+    /// The current piece became part of the board, filling `rows`, and was
+    /// scored as `clear`
+    Locked {
+        class: PieceClass,
+        rows: Vec<isize>,
+        clear: Clear,
+    },
+    /// Full rows were removed after their animation
+    LinesCleared {
+        rows: Vec<isize>,
+        clear: Clear,
+    },
+    /// Rows of garbage were pushed up from the bottom of the board
+    GarbageAdded(usize),
+    LevelUp(usize),
+    ScoreChanged(usize),
+    /// A piece came into play overlapping the stack, ending the game
+    ToppedOut,
+}
@@ -1,8 +1,12 @@
 use super::{
-    board::Board,
-    piece::{Piece, PieceGenerator},
+    board::{Board, BOARD_HEIGHT, BOARD_WIDTH},
+    event::Event,
+    mode::GameMode,
+    piece::{Piece, PieceClass, PieceGenerator},
+    random::MersenneTwister,
+    ruleset::Ruleset,
+    scoring::{t_spin, Clear, Lock, ScoringSystem},
 };
-use colosseum::{Input, Texture, Window};
 
 pub enum ARE {
     None,
@@ -11,44 +15,84 @@ pub enum ARE {
 }
 
 pub struct Game {
+    mode: GameMode,
+    ruleset: Ruleset,
     board: Board,
     level: usize,
     score: usize,
     lines_cleared: usize,
     lines_target: usize,
     total_lines: usize,
+    frames: usize,
     current_piece: Option<Piece>,
-    next_piece: Piece,
+    held_piece: Option<Piece>,
+    hold_used: bool,
     piece_generator: PieceGenerator,
-    texture: Texture,
+    // Picks the holes in garbage rows, seeded from the game's seed so
+    // replays get the same garbage
+    garbage_random: MersenneTwister,
+    // Garbage rows still on the board. They always sit at the bottom, as
+    // pieces can only land on top of them.
+    garbage_rows: usize,
+    // Garbage rows the game started with
+    starting_garbage_rows: usize,
+    scoring: Box<dyn ScoringSystem>,
+    // The offset of the last rotation, while nothing else has moved the
+    // piece since
+    last_kick: Option<(isize, isize)>,
+    // The clear being animated, scored when the lines are gone
+    pending_clear: Option<Clear>,
+    events: Vec<Event>,
 }
 
 const DROP_TIMES: &[u8] = &[
     48, 43, 38, 33, 28, 23, 18, 13, 8, 6, 5, 5, 5, 4, 4, 4, 3, 3, 3,
 ];
 
-const MAX_SCORE: usize = 999999;
+const HARD_DROP_POINTS: usize = 2;
 
-impl Game {
-    pub fn new<I: Input>(starting_level: usize, texture: Texture, window: &mut Window<I>) -> Self {
-        let mut piece_generator = PieceGenerator::from_time();
-        let mut current_piece =
-            Piece::new(piece_generator.next_piece_class(), texture.clone(), window);
-        current_piece.set_start_position();
+// Keeps the garbage holes from following the same sequence as the pieces
+const GARBAGE_SEED_SALT: u32 = 0x6A5D39E1;
 
-        Game {
+impl Game {
+    /// Creates a game whose pieces come from `piece_generator`, starting
+    /// with any garbage the mode calls for, `dig_rows` rows in Dig
+    pub fn new(
+        mode: GameMode,
+        ruleset: Ruleset,
+        starting_level: usize,
+        dig_rows: usize,
+        mut piece_generator: PieceGenerator,
+    ) -> Self {
+        let current_piece = Piece::new(piece_generator.next_piece_class());
+        let events = vec![Event::Spawned(current_piece.class())];
+
+        let mut game = Game {
+            mode,
+            ruleset,
             board: Board::new(),
             level: starting_level,
             score: 0,
             lines_cleared: 0,
             lines_target: (starting_level * 10 + 10)
                 .min((starting_level as isize * 10 - 50).max(100) as usize),
-                total_lines: 0,
+            total_lines: 0,
+            frames: 0,
             current_piece: Some(current_piece),
-            next_piece: Piece::new(piece_generator.next_piece_class(), texture.clone(), window),
+            held_piece: None,
+            hold_used: false,
+            garbage_random: MersenneTwister::new(piece_generator.seed() ^ GARBAGE_SEED_SALT),
+            garbage_rows: 0,
+            starting_garbage_rows: mode.garbage_rows(dig_rows),
             piece_generator,
-            texture,
-        }
+            scoring: ruleset.scoring_system(),
+            last_kick: None,
+            pending_clear: None,
+            events,
+        };
+
+        game.add_garbage(game.starting_garbage_rows);
+        game
     }
 
     pub fn drop_time(&self) -> u8 {
@@ -63,6 +107,18 @@ impl Game {
         }
     }
 
+    pub fn seed(&self) -> u32 {
+        self.piece_generator.seed()
+    }
+
+    pub fn mode(&self) -> GameMode {
+        self.mode
+    }
+
+    pub fn ruleset(&self) -> Ruleset {
+        self.ruleset
+    }
+
     pub fn score(&self) -> usize {
         self.score
     }
@@ -71,6 +127,47 @@ impl Game {
         self.total_lines
     }
 
+    /// Lines left to clear, if the mode has a goal. In modes that start with
+    /// garbage, only garbage rows count.
+    pub fn lines_remaining(&self) -> Option<usize> {
+        if self.starting_garbage_rows > 0 {
+            return Some(self.garbage_rows);
+        }
+
+        self.mode
+            .line_goal()
+            .map(|goal| goal.saturating_sub(self.total_lines))
+    }
+
+    pub fn garbage_rows(&self) -> usize {
+        self.garbage_rows
+    }
+
+    /// Frames played so far
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    /// The time shown on the HUD in frames, if the mode is timed
+    pub fn timer(&self) -> Option<usize> {
+        self.mode.timer(self.frames)
+    }
+
+    /// Counts one frame of play
+    pub fn tick(&mut self) {
+        self.frames += 1;
+    }
+
+    /// Whether the game has ended by reaching its line goal or running out
+    /// of time, rather than by topping out
+    pub fn is_complete(&self) -> bool {
+        self.lines_remaining() == Some(0)
+            || self
+                .mode
+                .time_limit()
+                .is_some_and(|limit| self.frames >= limit)
+    }
+
     pub fn level_lines(&self) -> usize {
         self.lines_cleared
     }
@@ -83,90 +180,204 @@ impl Game {
         self.piece_generator.stats()
     }
 
-    pub fn rotate_left(&mut self) {
-        self.current_piece.as_mut().map(|current_piece| {
-            current_piece.rotate_left();
-            if !self.board.verify(current_piece) {
-                current_piece.rotate_right();
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn current_piece(&self) -> Option<&Piece> {
+        self.current_piece.as_ref()
+    }
+
+    /// The pieces that will spawn after the current one, in order
+    pub fn next_queue(&self) -> &[PieceClass] {
+        self.piece_generator.queue()
+    }
+
+    pub fn held_piece(&self) -> Option<&Piece> {
+        self.held_piece.as_ref()
+    }
+
+    /// Takes the events since the last call, oldest first
+    pub fn take_events(&mut self) -> Vec<Event> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Pushes `rows` rows of garbage up from the bottom of the board, each
+    /// with a hole in a random column. The current piece is pushed up with
+    /// the stack if it would overlap it. Returns true if anything is pushed
+    /// off the top.
+    ///
+    /// Lines waiting to be cleared would move out from under their animation,
+    /// so this isn't called between a lock and the collapse.
+    pub fn add_garbage(&mut self, rows: usize) -> bool {
+        if rows == 0 {
+            return false;
+        }
+
+        for _ in 0..rows {
+            let hole = self.garbage_random.next_below(BOARD_WIDTH as u32) as usize;
+            let fits = self.board.push_garbage(hole);
+            self.garbage_rows = (self.garbage_rows + 1).min(BOARD_HEIGHT);
+
+            let piece_fits = match self.current_piece.as_mut() {
+                Some(current_piece) if !self.board.verify(current_piece) => {
+                    current_piece.move_up();
+                    self.board.verify(current_piece)
+                }
+                _ => true,
+            };
+
+            if !fits || !piece_fits {
+                self.events.push(Event::ToppedOut);
+                return true;
             }
-        });
+        }
+
+        self.events.push(Event::GarbageAdded(rows));
+        false
     }
 
-    pub fn rotate_right(&mut self) {
-        self.current_piece.as_mut().map(|current_piece| {
-            current_piece.rotate_right();
-            if !self.board.verify(current_piece) {
-                current_piece.rotate_left();
+    /// Swaps the current piece into the hold slot, bringing out the piece
+    /// that was there or the next piece if it was empty. Each piece can only
+    /// be held once. Returns true if the piece brought out doesn't fit.
+    pub fn hold(&mut self) -> bool {
+        if !self.ruleset.hold() || self.hold_used {
+            return false;
+        }
+
+        let current_piece = match self.current_piece.take() {
+            Some(current_piece) => current_piece,
+            None => return false,
+        };
+
+        self.events.push(Event::Held(current_piece.class()));
+        let game_over = match self
+            .held_piece
+            .replace(Piece::new(current_piece.class()))
+        {
+            Some(held_piece) => {
+                if self.board.verify(&held_piece) {
+                    self.events.push(Event::Spawned(held_piece.class()));
+                    self.current_piece = Some(held_piece);
+                    self.last_kick = None;
+                    false
+                } else {
+                    self.events.push(Event::ToppedOut);
+                    true
+                }
             }
-        });
+            None => self.finish_are(),
+        };
+
+        self.hold_used = true;
+        game_over
     }
 
-    pub fn move_left(&mut self) {
-        self.current_piece.as_mut().map(|current_piece| {
-            current_piece.move_left();
-            if !self.board.verify(current_piece) {
-                current_piece.move_right();
+    pub fn rotate_left(&mut self) {
+        self.rotate(false);
+    }
+
+    pub fn rotate_right(&mut self) {
+        self.rotate(true);
+    }
+
+    fn rotate(&mut self, clockwise: bool) {
+        let rotation_system = self.ruleset.rotation_system();
+        if let Some(current_piece) = self.current_piece.as_mut() {
+            if let Some(rotated) = rotation_system.rotate(current_piece, &self.board, clockwise) {
+                let (x, y) = current_piece.position();
+                let (rotated_x, rotated_y) = rotated.position();
+                self.last_kick = Some((rotated_x - x, rotated_y - y));
+                *current_piece = rotated;
+                self.events.push(Event::Rotated { clockwise });
             }
-        });
+        }
     }
 
-    pub fn move_right(&mut self) {
-        self.current_piece.as_mut().map(|current_piece| {
-            current_piece.move_right();
-            if !self.board.verify(current_piece) {
+    /// Returns false if there is no piece or something is in the way
+    pub fn move_left(&mut self) -> bool {
+        match self.current_piece.as_mut() {
+            Some(current_piece) => {
                 current_piece.move_left();
+                if self.board.verify(current_piece) {
+                    self.last_kick = None;
+                    self.events.push(Event::Moved { x: -1, y: 0 });
+                    true
+                } else {
+                    current_piece.move_right();
+                    false
+                }
             }
-        });
+            None => false,
+        }
+    }
+
+    /// Returns false if there is no piece or something is in the way
+    pub fn move_right(&mut self) -> bool {
+        match self.current_piece.as_mut() {
+            Some(current_piece) => {
+                current_piece.move_right();
+                if self.board.verify(current_piece) {
+                    self.last_kick = None;
+                    self.events.push(Event::Moved { x: 1, y: 0 });
+                    true
+                } else {
+                    current_piece.move_left();
+                    false
+                }
+            }
+            None => false,
+        }
     }
 
     pub fn collapse(&mut self, lines: &[isize]) {
         self.board.collapse(lines);
 
+        let first_garbage_row = (BOARD_HEIGHT - self.garbage_rows) as isize;
+        self.garbage_rows -= lines
+            .iter()
+            .filter(|line| **line >= first_garbage_row)
+            .count();
+
         // Update score
-        self.add_score(
-            (self.level + 1)
-                * match lines.len() {
-                    0 => 0,
-                    1 => 40,
-                    2 => 100,
-                    3 => 300,
-                    4 => 1200,
-                    _ => panic!("Impossible number of lines cleared!"),
-                },
-        );
+        let clear = self
+            .pending_clear
+            .take()
+            .expect("lines collapsed without a lock clearing them");
+        self.add_score(clear.points);
 
         // Update level
         self.lines_cleared += lines.len();
         self.total_lines += lines.len();
+        self.events.push(Event::LinesCleared {
+            rows: lines.to_vec(),
+            clear,
+        });
 
-        if self.lines_cleared >= self.lines_target {
+        if self.mode.levels_up() && self.lines_cleared >= self.lines_target {
             self.lines_cleared = 0;
             self.level += 1;
             self.lines_target += 10;
+            self.events.push(Event::LevelUp(self.level));
         }
     }
 
-    pub fn finish_are<I: Input>(&mut self, window: &mut Window<I>) -> bool {
-        // Generate new piece
-        let mut piece = Piece::new(
-            self.piece_generator.next_piece_class(),
-            self.texture.clone(),
-            window,
-        );
-
-        // Set it as the next piece
-        std::mem::swap(&mut self.next_piece, &mut piece);
-
-        // Update the new current piece position
-        piece.set_start_position();
+    pub fn finish_are(&mut self) -> bool {
+        // Take the next piece from the queue
+        let piece = Piece::new(self.piece_generator.next_piece_class());
 
         // Check for game over
         if !self.board.verify(&piece) {
+            self.events.push(Event::ToppedOut);
             return true;
         }
 
+        self.events.push(Event::Spawned(piece.class()));
+
         // Set the new current piece
         self.current_piece = Some(piece);
+        self.hold_used = false;
+        self.last_kick = None;
 
         false
     }
@@ -188,12 +399,57 @@ impl Game {
         }
 
         if verify {
+            self.last_kick = None;
+            self.events.push(Event::Moved { x: 0, y: 1 });
             return None;
         }
 
-        // Get current piece
-        let mut current_piece = self.current_piece.take().unwrap();
-        current_piece.move_up();
+        self.current_piece.as_mut().unwrap().move_up();
+        Some(self.lock())
+    }
+
+    /// Drops the current piece straight to its landing position and locks
+    /// it, if the ruleset allows hard drops
+    pub fn hard_drop(&mut self) -> Option<ARE> {
+        if !self.ruleset.hard_drop() {
+            return None;
+        }
+
+        let landing_piece = self.landing_piece()?;
+        let cells = landing_piece.position().1 - self.current_piece.as_ref()?.position().1;
+        self.add_score(cells as usize * HARD_DROP_POINTS);
+        if cells > 0 {
+            self.last_kick = None;
+            self.events.push(Event::Moved { x: 0, y: cells });
+        }
+
+        self.current_piece = Some(landing_piece);
+        Some(self.lock())
+    }
+
+    /// The current piece moved as far down as it can go
+    pub fn landing_piece(&self) -> Option<Piece> {
+        let mut piece = self.current_piece.clone()?;
+        while self.board.verify(&piece) {
+            piece.move_down();
+        }
+        piece.move_up();
+
+        Some(piece)
+    }
+
+    /// Where the ghost piece is drawn, if the ruleset shows one
+    pub fn ghost_piece(&self) -> Option<Piece> {
+        if self.ruleset.ghost_piece() {
+            self.landing_piece()
+        } else {
+            None
+        }
+    }
+
+    // Adds the current piece to the board
+    fn lock(&mut self) -> ARE {
+        let current_piece = self.current_piece.take().unwrap();
         let line_locked = {
             let mut lowest_y = current_piece.get_tile_position(0).1;
 
@@ -208,10 +464,34 @@ impl Game {
         };
 
         // Effect the board
+        let class = current_piece.class();
+        let t_spin = t_spin(&current_piece, &self.board, self.last_kick);
         self.board.finalize(current_piece);
         let lines_cleared = self.board.check();
 
-        Some(if lines_cleared.len() == 0 {
+        // The lock is scored now so it can be announced straight away, but
+        // the points for clearing lines wait until the lines are gone
+        let clear = self.scoring.score(
+            Lock {
+                lines: lines_cleared.len(),
+                t_spin,
+                perfect_clear: !lines_cleared.is_empty()
+                    && self.board.is_empty_without(&lines_cleared),
+            },
+            self.level,
+        );
+        self.events.push(Event::Locked {
+            class,
+            rows: lines_cleared.clone(),
+            clear,
+        });
+        if lines_cleared.is_empty() {
+            self.add_score(clear.points);
+        } else {
+            self.pending_clear = Some(clear);
+        }
+
+        if lines_cleared.is_empty() {
             ARE::ARE(if line_locked < 2 {
                 10
             } else {
@@ -219,26 +499,385 @@ impl Game {
             })
         } else {
             ARE::LineDelay(0, lines_cleared)
-        })
-    }
-
-    pub fn render<I: Input>(&mut self, window: &mut Window<I>) {
-        self.board.render(window);
-        match self.current_piece.as_mut() {
-            Some(current_piece) => current_piece.render(window),
-            None => {}
         }
-        self.next_piece.render(window);
     }
 
     pub fn clear_animation(&mut self, step: u8, lines: &[isize]) {
         self.board.clear_animation(step as isize, lines)
     }
 
+    // The score isn't capped here; the counter on screen decides whether it
+    // stops at 999999
     fn add_score(&mut self, score: usize) {
-        self.score += score;
-        if self.score > MAX_SCORE {
-            self.score = MAX_SCORE;
+        if score > 0 {
+            self.score += score;
+            self.events.push(Event::ScoreChanged(self.score));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{
+        mode::{DEFAULT_DIG_ROWS, FRAMES_PER_SECOND},
+        randomizer::RandomizerKind,
+        scoring::TSpin,
+        tile::TileColor,
+    };
+
+    fn new_game(mode: GameMode) -> Game {
+        Game::new(
+            mode,
+            Ruleset::Modern,
+            0,
+            DEFAULT_DIG_ROWS,
+            PieceGenerator::new(7, RandomizerKind::SevenBag, 1),
+        )
+    }
+
+    /// Fills in `rows` and clears them the way a lock would
+    fn clear_rows(game: &mut Game, rows: &[isize]) {
+        for y in rows {
+            for x in 0..BOARD_WIDTH as isize {
+                if game.board.get(x, *y).unwrap().is_none() {
+                    game.board.set(x, *y, Some(TileColor::Red));
+                }
+            }
+        }
+
+        let lines = game.board.check();
+        assert_eq!(lines, rows);
+        game.pending_clear = Some(game.scoring.score(
+            Lock {
+                lines: lines.len(),
+                t_spin: TSpin::None,
+                perfect_clear: false,
+            },
+            game.level,
+        ));
+        game.collapse(&lines);
+    }
+
+    fn assert_garbage_rows(board: &Board, rows: usize) {
+        for y in 0..BOARD_HEIGHT {
+            let tiles: Vec<_> = (0..BOARD_WIDTH)
+                .map(|x| board.get(x as isize, y as isize).unwrap())
+                .collect();
+            let holes = tiles.iter().filter(|tile| tile.is_none()).count();
+
+            if y < BOARD_HEIGHT - rows {
+                assert_eq!(holes, BOARD_WIDTH, "row {} isn't empty", y);
+            } else {
+                assert_eq!(holes, 1, "row {} has {} holes", y, holes);
+                assert!(tiles
+                    .iter()
+                    .all(|tile| tile.is_none() || *tile == Some(TileColor::Garbage)));
+            }
+        }
+    }
+
+    #[test]
+    fn garbage_rows_have_one_hole() {
+        let mut game = new_game(GameMode::Dig);
+        assert_eq!(game.garbage_rows(), 10);
+        assert_garbage_rows(game.board(), 10);
+        assert!(game.take_events().contains(&Event::GarbageAdded(10)));
+
+        let game = Game::new(
+            GameMode::Dig,
+            Ruleset::Modern,
+            0,
+            3,
+            PieceGenerator::new(7, RandomizerKind::SevenBag, 1),
+        );
+        assert_eq!(game.lines_remaining(), Some(3));
+        assert_garbage_rows(game.board(), 3);
+
+        let mut game = new_game(GameMode::Marathon);
+        assert_eq!(game.garbage_rows(), 0);
+        assert!(!game.add_garbage(4));
+        assert_eq!(game.garbage_rows(), 4);
+        assert_garbage_rows(game.board(), 4);
+    }
+
+    #[test]
+    fn dig_ends_when_the_last_garbage_row_clears() {
+        let mut game = new_game(GameMode::Dig);
+        let bottom = BOARD_HEIGHT as isize - 1;
+
+        // Clearing a row above the garbage doesn't count
+        clear_rows(&mut game, &[bottom - 10]);
+        assert_eq!(game.lines_remaining(), Some(10));
+
+        // Nor does the part of a clear above the garbage
+        clear_rows(&mut game, &[bottom - 10, bottom - 9]);
+        assert_eq!(game.lines_remaining(), Some(9));
+
+        clear_rows(&mut game, &[bottom - 3, bottom]);
+        assert_eq!(game.lines_remaining(), Some(7));
+
+        while game.garbage_rows() > 1 {
+            assert!(!game.is_complete());
+            clear_rows(&mut game, &[bottom]);
+        }
+        assert_eq!(game.lines_remaining(), Some(1));
+        assert!(!game.is_complete());
+
+        clear_rows(&mut game, &[bottom]);
+        assert_eq!(game.lines_remaining(), Some(0));
+        assert!(game.is_complete());
+        assert!(game.board().is_empty());
+    }
+
+    #[test]
+    fn garbage_pushing_the_stack_off_the_top_tops_out() {
+        let mut game = new_game(GameMode::Marathon);
+        game.current_piece = None;
+        game.take_events();
+        game.board.set(0, 1, Some(TileColor::Red));
+
+        assert!(!game.add_garbage(1));
+        assert_eq!(game.take_events(), [Event::GarbageAdded(1)]);
+
+        assert!(game.add_garbage(3));
+        assert_eq!(game.take_events(), [Event::ToppedOut]);
+        assert_eq!(game.garbage_rows(), 2);
+    }
+
+    #[test]
+    fn garbage_pushing_the_piece_into_the_stack_tops_out() {
+        let mut game = new_game(GameMode::Marathon);
+        let piece = game.current_piece.as_mut().unwrap();
+        for _ in 0..3 {
+            piece.move_down();
+        }
+        let piece = piece.clone();
+
+        // A ledge right under the piece
+        let below = (0..4).map(|i| piece.get_tile_position(i).1).max().unwrap() + 1;
+        for x in 0..BOARD_WIDTH as isize - 1 {
+            game.board.set(x, below, Some(TileColor::Red));
+        }
+
+        // The piece is pushed up with the stack until it runs out of room
+        assert!(!game.add_garbage(1));
+        assert_eq!(
+            game.current_piece().unwrap().position().1,
+            piece.position().1 - 1
+        );
+
+        let mut rows = 1;
+        while !game.add_garbage(1) {
+            rows += 1;
+            assert!(rows < BOARD_HEIGHT, "never topped out");
+        }
+        assert_eq!(game.take_events().last(), Some(&Event::ToppedOut));
+    }
+
+    fn cells(piece: &Piece) -> Vec<(isize, isize)> {
+        (0..4).map(|i| piece.get_tile_position(i)).collect()
+    }
+
+    #[test]
+    fn hard_drop_lands_on_the_ghost_and_locks() {
+        let mut game = new_game(GameMode::Marathon);
+        let piece = game.current_piece().unwrap().clone();
+
+        // Something to land on partway down
+        let (x, _) = piece.get_tile_position(0);
+        game.board.set(x, 12, Some(TileColor::Red));
+        game.take_events();
+
+        let ghost = game.ghost_piece().unwrap();
+        let cells_dropped = ghost.position().1 - piece.position().1;
+        assert!(cells_dropped > 0);
+        assert!(cells(&ghost).iter().any(|&(_, y)| y == 11));
+
+        assert!(matches!(game.hard_drop(), Some(ARE::ARE(_))));
+        assert_eq!(game.score(), cells_dropped as usize * HARD_DROP_POINTS);
+        assert!(game.current_piece().is_none());
+        for (x, y) in cells(&ghost) {
+            assert!(game.board().get(x, y).unwrap().is_some());
+        }
+
+        let events = game.take_events();
+        assert!(events.contains(&Event::Moved {
+            x: 0,
+            y: cells_dropped
+        }));
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, Event::Locked { class, .. } if *class == piece.class())));
+    }
+
+    #[test]
+    fn classic_has_no_hard_drop() {
+        let mut game = Game::new(
+            GameMode::Marathon,
+            Ruleset::Classic,
+            0,
+            DEFAULT_DIG_ROWS,
+            PieceGenerator::new(7, RandomizerKind::SevenBag, 1),
+        );
+        assert!(game.ghost_piece().is_none());
+        assert!(game.hard_drop().is_none());
+        assert!(game.current_piece().is_some());
+        assert_eq!(game.score(), 0);
+    }
+
+    #[test]
+    fn hold_once_per_piece() {
+        let mut game = new_game(GameMode::Marathon);
+        let first = game.current_piece().unwrap().class();
+        let spawned = game.current_piece().unwrap().clone();
+        let next = game.next_queue()[0];
+
+        // The first hold brings out the next piece
+        game.take_events();
+        assert!(!game.hold());
+        assert_eq!(game.held_piece().unwrap().class(), first);
+        assert_eq!(game.current_piece().unwrap().class(), next);
+        assert_eq!(
+            game.take_events(),
+            [Event::Held(first), Event::Spawned(next)]
+        );
+
+        // A second hold before the piece locks does nothing
+        assert!(!game.hold());
+        assert_eq!(game.held_piece().unwrap().class(), first);
+        assert_eq!(game.current_piece().unwrap().class(), next);
+        assert!(game.take_events().is_empty());
+
+        assert!(game.hard_drop().is_some());
+        assert!(!game.finish_are());
+        let after_lock = game.current_piece().unwrap().class();
+
+        // Once it has, the held piece swaps back in where it first spawned
+        game.rotate_right();
+        game.move_left();
+        game.take_events();
+        assert!(!game.hold());
+        assert_eq!(game.held_piece().unwrap().class(), after_lock);
+        let swapped = game.current_piece().unwrap();
+        assert_eq!(swapped.class(), first);
+        assert_eq!(swapped.orientation(), spawned.orientation());
+        assert_eq!(swapped.position(), spawned.position());
+        assert_eq!(
+            game.take_events(),
+            [Event::Held(after_lock), Event::Spawned(first)]
+        );
+
+        // The piece put in the hold is kept in its spawn orientation too
+        assert_eq!(game.held_piece().unwrap().orientation(), 0);
+    }
+
+    #[test]
+    fn sprint_ends_at_forty_lines() {
+        let mut game = new_game(GameMode::Sprint);
+        let bottom = BOARD_HEIGHT as isize - 1;
+        assert_eq!(game.lines_remaining(), Some(40));
+
+        for _ in 0..9 {
+            clear_rows(&mut game, &[bottom - 3, bottom - 2, bottom - 1, bottom]);
+        }
+        clear_rows(&mut game, &[bottom - 2, bottom - 1, bottom]);
+        assert_eq!(game.lines_remaining(), Some(1));
+        assert!(!game.is_complete());
+
+        // A clear past the goal still ends it
+        clear_rows(&mut game, &[bottom - 1, bottom]);
+        assert_eq!(game.total_lines(), 41);
+        assert_eq!(game.lines_remaining(), Some(0));
+        assert!(game.is_complete());
+
+        // Sprint never levels up
+        assert_eq!(game.level(), 0);
+    }
+
+    #[test]
+    fn ultra_ends_after_two_minutes() {
+        let mut game = new_game(GameMode::Ultra);
+        assert_eq!(game.lines_remaining(), None);
+        assert_eq!(game.timer(), Some(2 * 60 * FRAMES_PER_SECOND));
+
+        for _ in 0..2 * 60 * FRAMES_PER_SECOND - 1 {
+            game.tick();
         }
+        assert_eq!(game.timer(), Some(1));
+        assert!(!game.is_complete());
+
+        game.tick();
+        assert_eq!(game.timer(), Some(0));
+        assert!(game.is_complete());
+
+        // The clock stops at zero
+        game.tick();
+        assert_eq!(game.timer(), Some(0));
+    }
+
+    #[test]
+    fn timer_counts_frames() {
+        let mut sprint = new_game(GameMode::Sprint);
+        let mut marathon = new_game(GameMode::Marathon);
+        assert_eq!(sprint.timer(), Some(0));
+        for _ in 0..90 {
+            sprint.tick();
+            marathon.tick();
+        }
+
+        assert_eq!(sprint.frames(), 90);
+        assert_eq!(sprint.timer(), Some(90));
+        assert!(!sprint.is_complete());
+
+        // Untimed modes count frames without showing a clock or ending
+        assert_eq!(marathon.frames(), 90);
+        assert_eq!(marathon.timer(), None);
+        assert!(!marathon.is_complete());
+    }
+
+    #[test]
+    fn events_come_in_the_order_they_happen() {
+        let mut game = new_game(GameMode::Marathon);
+        let class = game.current_piece().unwrap().class();
+        let next = game.next_queue()[0];
+
+        // One line short of level 1, with the bottom row full apart from
+        // where the piece lands
+        game.lines_cleared = 9;
+        let bottom = BOARD_HEIGHT as isize - 1;
+        let landing = cells(&game.landing_piece().unwrap());
+        for x in 0..BOARD_WIDTH as isize {
+            if !landing.contains(&(x, bottom)) {
+                game.board.set(x, bottom, Some(TileColor::Red));
+            }
+        }
+        game.take_events();
+
+        let lines = match game.hard_drop() {
+            Some(ARE::LineDelay(_, lines)) => lines,
+            _ => panic!("the hard drop didn't clear a line"),
+        };
+        assert_eq!(lines, [bottom]);
+        game.collapse(&lines);
+        assert!(!game.finish_are());
+
+        let events = game.take_events();
+        assert!(
+            matches!(
+                events.as_slice(),
+                [
+                    Event::ScoreChanged(_),
+                    Event::Moved { x: 0, .. },
+                    Event::Locked { class: locked, rows: locked_rows, .. },
+                    Event::ScoreChanged(_),
+                    Event::LinesCleared { rows, .. },
+                    Event::LevelUp(1),
+                    Event::Spawned(spawned),
+                ] if *locked == class && *locked_rows == lines && *rows == lines && *spawned == next
+            ),
+            "{:?}",
+            events
+        );
     }
 }
@@ -0,0 +1,83 @@
+/// Something the player can ask for, independent of the key that asks for it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    SoftDrop,
+    RotateCounterclockwise,
+    RotateClockwise,
+    HardDrop,
+    Hold,
+    Pause,
+}
+
+/// The actions held during one frame of play
+///
+/// Pausing is handled outside the simulation, so `Action::Pause` is never
+/// part of a frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InputFrame(u8);
+
+impl Action {
+    pub const ALL: [Action; 8] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::SoftDrop,
+        Action::RotateCounterclockwise,
+        Action::RotateClockwise,
+        Action::HardDrop,
+        Action::Hold,
+        Action::Pause,
+    ];
+
+    /// The name used for this action in configuration files
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::SoftDrop => "soft_drop",
+            Action::RotateCounterclockwise => "rotate_counterclockwise",
+            Action::RotateClockwise => "rotate_clockwise",
+            Action::HardDrop => "hard_drop",
+            Action::Hold => "hold",
+            Action::Pause => "pause",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+impl InputFrame {
+    /// Returns `None` if `bits` holds actions that can't be part of a frame
+    pub fn from_bits(bits: u8) -> Option<Self> {
+        if bits & Action::Pause.bit() == 0 {
+            Some(InputFrame(bits))
+        } else {
+            None
+        }
+    }
+
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+
+    pub fn is_held(self, action: Action) -> bool {
+        self.0 & action.bit() != 0
+    }
+
+    /// Whether `action` is held on this frame but wasn't on `previous`
+    pub fn just_pressed(self, previous: InputFrame, action: Action) -> bool {
+        self.is_held(action) && !previous.is_held(action)
+    }
+
+    pub fn hold(&mut self, action: Action) {
+        assert!(action != Action::Pause);
+        self.0 |= action.bit();
+    }
+}
@@ -1,8 +1,38 @@
+//! The rules of the game, free of any rendering or windowing types so they can
+//! be driven by the renderer, tests, bots or servers alike.
+
+mod attack;
 mod board;
+mod event;
 mod game;
+mod input;
+mod mode;
 mod piece;
+mod random;
+mod randomizer;
+mod rotation;
+mod ruleset;
+mod scoring;
+mod simulation;
 mod tile;
+mod versus;
 
-pub use board::{BOARD_HEIGHT, BOARD_WIDTH};
+pub use attack::AttackTable;
+pub use board::{Board, BOARD_HEIGHT, BOARD_WIDTH};
+pub use event::Event;
 pub use game::{ARE, Game};
+pub use input::{Action, InputFrame};
+pub use mode::{GameMode, DEFAULT_DIG_ROWS, FRAMES_PER_SECOND, MAX_DIG_ROWS};
+pub use piece::{Piece, PieceClass, PieceGenerator, MAX_NEXT_QUEUE_LENGTH};
+pub use random::MersenneTwister;
+pub use randomizer::{
+    FixedSequence, FourteenBag, NesRandomizer, Randomizer, RandomizerKind, SevenBag, Tgm, Uniform,
+};
+pub use rotation::{NesRotation, RotationSystem, SuperRotation};
+pub use ruleset::Ruleset;
+pub use scoring::{
+    t_spin, Clear, GuidelineScoring, Lock, NesScoring, ScoringSystem, TSpin,
+};
+pub use simulation::{DasSettings, Simulation};
 pub use tile::TileColor;
+pub use versus::{Outcome, VersusGame};
@@ -0,0 +1,104 @@
+use super::board::BOARD_HEIGHT;
+
+/// What a game is played for: whether the level rises, when the game is won
+/// and which counters the HUD shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameMode {
+    /// Endless play, levelling up every 10 lines, as the NES A-type
+    Marathon,
+    /// Clear 25 lines at a fixed level, as the NES B-type
+    BType,
+    /// Clear 40 lines as fast as possible
+    Sprint,
+    /// Score as much as possible in two minutes
+    Ultra,
+    /// Clear away rows of garbage as fast as possible
+    Dig,
+}
+
+/// The frame rate every game runs at
+pub const FRAMES_PER_SECOND: usize = 60;
+
+const B_TYPE_LINES: usize = 25;
+const SPRINT_LINES: usize = 40;
+const ULTRA_FRAMES: usize = 2 * 60 * FRAMES_PER_SECOND;
+
+/// Garbage rows Dig starts with unless the config says otherwise
+pub const DEFAULT_DIG_ROWS: usize = 10;
+/// The most garbage rows Dig can start with, leaving room for pieces to spawn
+/// and move
+pub const MAX_DIG_ROWS: usize = BOARD_HEIGHT - 4;
+
+impl GameMode {
+    /// Every mode, in the order they are stored in replays
+    pub const ALL: [GameMode; 5] = [
+        GameMode::Marathon,
+        GameMode::BType,
+        GameMode::Sprint,
+        GameMode::Ultra,
+        GameMode::Dig,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GameMode::Marathon => "marathon",
+            GameMode::BType => "b_type",
+            GameMode::Sprint => "sprint",
+            GameMode::Ultra => "ultra",
+            GameMode::Dig => "dig",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<GameMode> {
+        GameMode::ALL
+            .into_iter()
+            .find(|mode| mode.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Whether clearing lines raises the level
+    pub fn levels_up(self) -> bool {
+        self == GameMode::Marathon
+    }
+
+    /// Whether games go in the high score table. It ranks by score, so the
+    /// timed modes and Dig are left out.
+    pub fn keeps_high_scores(self) -> bool {
+        matches!(self, GameMode::Marathon | GameMode::BType)
+    }
+
+    /// Lines that must be cleared to win
+    pub fn line_goal(self) -> Option<usize> {
+        match self {
+            GameMode::BType => Some(B_TYPE_LINES),
+            GameMode::Sprint => Some(SPRINT_LINES),
+            GameMode::Marathon | GameMode::Ultra | GameMode::Dig => None,
+        }
+    }
+
+    /// Garbage rows on the board at the start, all of which must be cleared
+    /// to win, where Dig starts with `dig_rows`
+    pub fn garbage_rows(self, dig_rows: usize) -> usize {
+        match self {
+            GameMode::Dig => dig_rows,
+            GameMode::Marathon | GameMode::BType | GameMode::Sprint | GameMode::Ultra => 0,
+        }
+    }
+
+    /// Frames the game lasts before it ends on its own
+    pub fn time_limit(self) -> Option<usize> {
+        match self {
+            GameMode::Ultra => Some(ULTRA_FRAMES),
+            GameMode::Marathon | GameMode::BType | GameMode::Sprint | GameMode::Dig => None,
+        }
+    }
+
+    /// The time the HUD shows after `frames` frames of play: the time taken
+    /// for Sprint and Dig, the time left for Ultra and nothing otherwise
+    pub fn timer(self, frames: usize) -> Option<usize> {
+        match self {
+            GameMode::Sprint | GameMode::Dig => Some(frames),
+            GameMode::Ultra => Some(ULTRA_FRAMES.saturating_sub(frames)),
+            GameMode::Marathon | GameMode::BType => None,
+        }
+    }
+}
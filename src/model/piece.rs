@@ -1,12 +1,13 @@
-use colosseum::{Input, Texture, Window};
 use std::time::SystemTime;
 
 use super::{
     board::BOARD_WIDTH,
-    tile::{Tile, TileColor},
+    random::MersenneTwister,
+    randomizer::{Randomizer, RandomizerKind},
+    tile::TileColor,
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(usize)]
 pub enum PieceClass {
     T,
@@ -18,33 +19,27 @@ pub enum PieceClass {
     I,
 }
 
-struct PieceTile {
-    tile: Tile,
-    offset: (isize, isize),
-}
-
+#[derive(Clone)]
 pub struct Piece {
-    tiles: [PieceTile; 4],
+    class: PieceClass,
+    offsets: [(isize, isize); 4],
     position: (isize, isize),
     even: bool,
-    modified: bool,
+    orientation: usize,
 }
 
 pub struct PieceGenerator {
-    mt: [u32; GEN_SIZE],
-    mt_tempered: [u32; GEN_SIZE],
-    index: usize,
-    current_permutation: Vec<PieceClass>,
+    seed: u32,
+    rng: MersenneTwister,
+    randomizer: Box<dyn Randomizer>,
+    queue: Vec<PieceClass>,
 
     stats: [usize; 7],
 }
 
-const GEN_SIZE: usize = 624;
-const PERIOD: usize = 397;
-const DIFF: usize = GEN_SIZE - PERIOD;
-const MAGIC: u32 = 0x9908B0DF;
+/// The most pieces that can be shown ahead of the current one
+pub const MAX_NEXT_QUEUE_LENGTH: usize = 6;
 
-const PREVIEW_POSITION: (isize, isize) = (-5, 4);
 const DEFAULT_POSITION: (isize, isize) = (BOARD_WIDTH as isize / 2 - 1, 1);
 
 fn convert_even_x(x: isize, even: bool) -> isize {
@@ -75,263 +70,186 @@ fn convert_even_y(y: isize, even: bool) -> isize {
     }
 }
 
-fn sum_offsets(position: (isize, isize), offset: (isize, isize), even: bool) -> (isize, isize) {
-    (
-        position.0 + convert_even_x(offset.0, even),
-        position.1 + convert_even_y(offset.1, even),
-    )
+impl PieceClass {
+    /// Every class, in the order the NES numbers them
+    pub const ALL: [PieceClass; 7] = [
+        PieceClass::T,
+        PieceClass::J,
+        PieceClass::Z,
+        PieceClass::O,
+        PieceClass::S,
+        PieceClass::L,
+        PieceClass::I,
+    ];
+
+    pub fn color(&self) -> TileColor {
+        match self {
+            PieceClass::I => TileColor::Cyan,
+            PieceClass::O => TileColor::Blue,
+            PieceClass::T => TileColor::Orange,
+            PieceClass::S => TileColor::Yellow,
+            PieceClass::Z => TileColor::Green,
+            PieceClass::J => TileColor::Purple,
+            PieceClass::L => TileColor::Red,
+        }
+    }
 }
 
 impl Piece {
-    pub fn new<I: Input>(class: PieceClass, texture: Texture, window: &mut Window<I>) -> Self {
-        let (offsets, even, color) = match class {
-            PieceClass::I => (
-                [(-3, -1), (-1, -1), (1, -1), (3, -1)],
-                true,
-                TileColor::Cyan,
-            ),
-            PieceClass::O => ([(-1, -1), (1, -1), (-1, 1), (1, 1)], true, TileColor::Blue),
-            PieceClass::T => ([(-1, 0), (0, 0), (1, 0), (0, -1)], false, TileColor::Orange),
-            PieceClass::S => (
-                [(-1, 0), (0, 0), (0, -1), (1, -1)],
-                false,
-                TileColor::Yellow,
-            ),
-            PieceClass::Z => ([(-1, -1), (0, -1), (0, 0), (1, 0)], false, TileColor::Green),
-            PieceClass::J => (
-                [(-1, -1), (-1, 0), (0, 0), (1, 0)],
-                false,
-                TileColor::Purple,
-            ),
-            PieceClass::L => ([(-1, 0), (0, 0), (1, 0), (1, -1)], false, TileColor::Red),
+    pub fn new(class: PieceClass) -> Self {
+        let (offsets, even) = match class {
+            PieceClass::I => ([(-3, -1), (-1, -1), (1, -1), (3, -1)], true),
+            PieceClass::O => ([(-1, -1), (1, -1), (-1, 1), (1, 1)], true),
+            PieceClass::T => ([(-1, 0), (0, 0), (1, 0), (0, -1)], false),
+            PieceClass::S => ([(-1, 0), (0, 0), (0, -1), (1, -1)], false),
+            PieceClass::Z => ([(-1, -1), (0, -1), (0, 0), (1, 0)], false),
+            PieceClass::J => ([(-1, -1), (-1, 0), (0, 0), (1, 0)], false),
+            PieceClass::L => ([(-1, 0), (0, 0), (1, 0), (1, -1)], false),
         };
 
         Piece {
-            tiles: offsets.map(|offset| PieceTile {
-                tile: Tile::new(
-                    color,
-                    sum_offsets(PREVIEW_POSITION, offset, even),
-                    texture.clone(),
-                    window,
-                ),
-                offset,
-            }),
-            position: PREVIEW_POSITION,
+            class,
+            offsets,
+            position: DEFAULT_POSITION,
             even,
-            modified: false,
+            orientation: 0,
         }
     }
 
-    pub fn get_tile_position(&self, tile: usize) -> (isize, isize) {
-        assert!(tile < 4);
-        sum_offsets(self.position, self.tiles[tile].offset, self.even)
+    pub fn class(&self) -> PieceClass {
+        self.class
     }
 
-    pub fn render<I: Input>(&mut self, window: &mut Window<I>) {
-        if self.modified {
-            self.modified = false;
-            self.update_positions();
-        }
+    pub fn color(&self) -> TileColor {
+        self.class.color()
+    }
 
-        for tile in &mut self.tiles {
-            tile.tile.render(window);
-        }
+    pub fn position(&self) -> (isize, isize) {
+        self.position
     }
 
-    pub fn set_start_position(&mut self) {
-        self.position = DEFAULT_POSITION;
-        self.update_positions();
+    /// Quarter turns clockwise from the spawn orientation, from 0 to 3
+    pub fn orientation(&self) -> usize {
+        self.orientation
+    }
+
+    /// Offset of `tile` from the piece position, in board cells
+    pub fn get_tile_offset(&self, tile: usize) -> (isize, isize) {
+        assert!(tile < 4);
+        (
+            convert_even_x(self.offsets[tile].0, self.even),
+            convert_even_y(self.offsets[tile].1, self.even),
+        )
+    }
+
+    pub fn get_tile_position(&self, tile: usize) -> (isize, isize) {
+        let offset = self.get_tile_offset(tile);
+        (self.position.0 + offset.0, self.position.1 + offset.1)
     }
 
     pub fn rotate_right(&mut self) {
-        self.modified = true;
-        for tile in &mut self.tiles {
-            let old_x = tile.offset.0;
-            tile.offset.0 = -tile.offset.1;
-            tile.offset.1 = old_x;
+        for offset in &mut self.offsets {
+            let old_x = offset.0;
+            offset.0 = -offset.1;
+            offset.1 = old_x;
         }
+        self.orientation = (self.orientation + 1) % 4;
     }
 
     pub fn rotate_left(&mut self) {
-        self.modified = true;
-        for tile in &mut self.tiles {
-            let old_x = tile.offset.0;
-            tile.offset.0 = tile.offset.1;
-            tile.offset.1 = -old_x;
+        for offset in &mut self.offsets {
+            let old_x = offset.0;
+            offset.0 = offset.1;
+            offset.1 = -old_x;
         }
+        self.orientation = (self.orientation + 3) % 4;
     }
 
     pub fn move_left(&mut self) {
-        self.modified = true;
         self.position.0 -= 1;
     }
 
     pub fn move_right(&mut self) {
-        self.modified = true;
         self.position.0 += 1;
     }
 
     pub fn move_down(&mut self) {
-        self.modified = true;
         self.position.1 += 1;
     }
 
     pub fn move_up(&mut self) {
-        self.modified = true;
         self.position.1 -= 1;
     }
 
-    pub fn finalize(self) -> [(isize, isize, Tile); 4] {
-        let mut result = [None, None, None, None];
+    pub fn translate(&mut self, x: isize, y: isize) {
+        self.position.0 += x;
+        self.position.1 += y;
+    }
+}
 
-        let mut i = 0;
-        for tile in self.tiles {
-            let (x, y) = sum_offsets(self.position, tile.offset, self.even);
-            result[i] = Some((x, y, tile.tile));
+impl PieceGenerator {
+    /// Creates a generator that keeps `queue_length` pieces decided ahead
+    pub fn new(seed: u32, randomizer: RandomizerKind, queue_length: usize) -> Self {
+        PieceGenerator::with_randomizer(seed, randomizer.create(), queue_length)
+    }
+
+    pub fn with_randomizer(
+        seed: u32,
+        randomizer: Box<dyn Randomizer>,
+        queue_length: usize,
+    ) -> Self {
+        assert!((1..=MAX_NEXT_QUEUE_LENGTH).contains(&queue_length));
+
+        let mut generator = PieceGenerator {
+            seed,
+            rng: MersenneTwister::new(seed),
+            randomizer,
+            queue: Vec::with_capacity(queue_length),
+            stats: [0; 7],
+        };
 
-            i += 1;
+        for _ in 0..queue_length {
+            let class = generator.randomizer.next_piece_class(&mut generator.rng);
+            generator.queue.push(class);
         }
 
-        result.map(|val| val.unwrap())
+        generator
     }
 
-    fn update_positions(&mut self) {
-        for tile in &mut self.tiles {
-            tile.tile
-                .set_position(sum_offsets(self.position, tile.offset, self.even))
-        }
+    pub fn from_time(randomizer: RandomizerKind, queue_length: usize) -> Self {
+        PieceGenerator::new(PieceGenerator::time_seed(), randomizer, queue_length)
     }
-}
 
-impl PieceGenerator {
-    pub fn new(seed: u32) -> Self {
-        let mut mt = [0u32; GEN_SIZE];
-        mt[0] = seed;
-
-        for i in 1..GEN_SIZE {
-            mt[i] = 0x6C078965u32
-                .wrapping_mul(mt[i - 1] ^ mt[i - 1].wrapping_shr(30))
-                .wrapping_add(i as u32);
-        }
-
-        PieceGenerator {
-            mt,
-            mt_tempered: [0; GEN_SIZE],
-            index: GEN_SIZE,
-            current_permutation: Vec::with_capacity(7),
-            stats: [0; 7],
-        }
+    /// A seed taken from the wall clock
+    pub fn time_seed() -> u32 {
+        (SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_millis()
+            & 0xFFFFFFFF) as u32
     }
 
-    pub fn from_time() -> Self {
-        PieceGenerator::new(
-            (SystemTime::now()
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .unwrap()
-                .as_millis()
-                & 0xFFFFFFFF) as u32,
-        )
+    pub fn seed(&self) -> u32 {
+        self.seed
     }
 
     pub fn stats(&self) -> &[usize] {
         &self.stats
     }
 
+    /// The pieces that will be handed out next, in order
+    pub fn queue(&self) -> &[PieceClass] {
+        &self.queue
+    }
+
+    /// Takes the piece at the front of the queue, deciding a new one to
+    /// replace it
     pub fn next_piece_class(&mut self) -> PieceClass {
-        if self.current_permutation.len() == 0 {
-            self.generate_permuation();
-        }
+        let class = self.randomizer.next_piece_class(&mut self.rng);
+        self.queue.push(class);
 
-        let ret = self.current_permutation.pop().unwrap();
+        let ret = self.queue.remove(0);
         self.stats[ret as usize] += 1;
         ret
     }
-
-    fn generate_permuation(&mut self) {
-        const OPTIONS_2: u32 = 2;
-        const OPTIONS_3: u32 = 3 * OPTIONS_2;
-        const OPTIONS_4: u32 = 4 * OPTIONS_3;
-        const OPTIONS_5: u32 = 5 * OPTIONS_4;
-        const OPTIONS_6: u32 = 6 * OPTIONS_5;
-        const OPTIONS_7: u32 = 7 * OPTIONS_6;
-
-        let mut value = self.next_number() % OPTIONS_7;
-        let index_6 = value / OPTIONS_6;
-        value -= index_6 * OPTIONS_6;
-        let index_5 = value / OPTIONS_5;
-        value -= index_5 * OPTIONS_5;
-        let index_4 = value / OPTIONS_4;
-        value -= index_4 * OPTIONS_4;
-        let index_3 = value / OPTIONS_3;
-        value -= index_3 * OPTIONS_3;
-        let index_2 = value / OPTIONS_2;
-        value -= index_2 * OPTIONS_2;
-        let index_1 = value;
-
-        let mut remaining_pieces = vec![
-            PieceClass::I,
-            PieceClass::O,
-            PieceClass::T,
-            PieceClass::S,
-            PieceClass::Z,
-            PieceClass::J,
-            PieceClass::L,
-        ];
-
-        self.current_permutation
-            .push(remaining_pieces.remove(index_6 as usize));
-        self.current_permutation
-            .push(remaining_pieces.remove(index_5 as usize));
-        self.current_permutation
-            .push(remaining_pieces.remove(index_4 as usize));
-        self.current_permutation
-            .push(remaining_pieces.remove(index_3 as usize));
-        self.current_permutation
-            .push(remaining_pieces.remove(index_2 as usize));
-        self.current_permutation
-            .push(remaining_pieces.remove(index_1 as usize));
-        self.current_permutation
-            .push(remaining_pieces.pop().unwrap());
-    }
-
-    // Mersenne Twister 19937 Psuedo Random Number Generator
-    fn next_number(&mut self) -> u32 {
-        if self.index == GEN_SIZE {
-            self.generate_numbers();
-        }
-
-        self.index += 1;
-        self.mt_tempered[self.index - 1]
-    }
-
-    fn generate_numbers(&mut self) {
-        for i in 0..DIFF {
-            let y = (0x80000000 & self.mt[i]) | (0x7FFFFFFF & self.mt[i + 1]);
-            self.mt[i] = self.mt[i + PERIOD]
-                ^ y.wrapping_shr(1)
-                ^ (y.wrapping_shl(31).wrapping_shr(31) & MAGIC);
-        }
-
-        for i in DIFF..GEN_SIZE - 1 {
-            let y = (0x80000000 & self.mt[i]) | (0x7FFFFFFF & self.mt[i + 1]);
-            self.mt[i] = self.mt[i - DIFF]
-                ^ y.wrapping_shr(1)
-                ^ (y.wrapping_shl(31).wrapping_shr(31) & MAGIC);
-        }
-
-        let y = (0x80000000 & self.mt[GEN_SIZE - 1]) | (0x7FFFFFFF & self.mt[0]);
-        self.mt[GEN_SIZE - 1] =
-            self.mt[PERIOD - 1] ^ y.wrapping_shr(1) ^ (y.wrapping_shl(31).wrapping_shr(31) & MAGIC);
-
-        for i in 0..GEN_SIZE {
-            let mut y = self.mt[i];
-            y ^= y.wrapping_shr(11);
-            y ^= y.wrapping_shl(7) & 0x9D2C5680;
-            y ^= y.wrapping_shr(15) & 0xEFC60000;
-            y ^= y.wrapping_shl(18);
-            self.mt_tempered[i] = y;
-        }
-
-        self.index = 0;
-    }
 }
@@ -0,0 +1,87 @@
+/// Mersenne Twister 19937 Psuedo Random Number Generator
+pub struct MersenneTwister {
+    mt: [u32; GEN_SIZE],
+    mt_tempered: [u32; GEN_SIZE],
+    index: usize,
+}
+
+const GEN_SIZE: usize = 624;
+const PERIOD: usize = 397;
+const DIFF: usize = GEN_SIZE - PERIOD;
+const MAGIC: u32 = 0x9908B0DF;
+
+impl MersenneTwister {
+    pub fn new(seed: u32) -> Self {
+        let mut mt = [0u32; GEN_SIZE];
+        mt[0] = seed;
+
+        for i in 1..GEN_SIZE {
+            mt[i] = 0x6C078965u32
+                .wrapping_mul(mt[i - 1] ^ mt[i - 1].wrapping_shr(30))
+                .wrapping_add(i as u32);
+        }
+
+        MersenneTwister {
+            mt,
+            mt_tempered: [0; GEN_SIZE],
+            index: GEN_SIZE,
+        }
+    }
+
+    pub fn next_number(&mut self) -> u32 {
+        if self.index == GEN_SIZE {
+            self.generate_numbers();
+        }
+
+        self.index += 1;
+        self.mt_tempered[self.index - 1]
+    }
+
+    /// A number from 0 to `bound` - 1, each equally likely
+    ///
+    /// Plain `next_number() % bound` favours small results whenever `bound`
+    /// doesn't divide 2^32, so draws from the incomplete run at the top of the
+    /// range are thrown away.
+    pub fn next_below(&mut self, bound: u32) -> u32 {
+        assert!(bound > 0);
+
+        let limit = (1u64 << 32) / bound as u64 * bound as u64;
+        loop {
+            let number = self.next_number() as u64;
+            if number < limit {
+                return (number % bound as u64) as u32;
+            }
+        }
+    }
+
+    fn generate_numbers(&mut self) {
+        for i in 0..DIFF {
+            let y = (0x80000000 & self.mt[i]) | (0x7FFFFFFF & self.mt[i + 1]);
+            self.mt[i] = self.mt[i + PERIOD]
+                ^ y.wrapping_shr(1)
+                ^ (y.wrapping_shl(31).wrapping_shr(31) & MAGIC);
+        }
+
+        for i in DIFF..GEN_SIZE - 1 {
+            let y = (0x80000000 & self.mt[i]) | (0x7FFFFFFF & self.mt[i + 1]);
+            self.mt[i] = self.mt[i - DIFF]
+                ^ y.wrapping_shr(1)
+                ^ (y.wrapping_shl(31).wrapping_shr(31) & MAGIC);
+        }
+
+        let y = (0x80000000 & self.mt[GEN_SIZE - 1]) | (0x7FFFFFFF & self.mt[0]);
+        self.mt[GEN_SIZE - 1] =
+            self.mt[PERIOD - 1] ^ y.wrapping_shr(1) ^ (y.wrapping_shl(31).wrapping_shr(31) & MAGIC);
+
+        for i in 0..GEN_SIZE {
+            let mut y = self.mt[i];
+            y ^= y.wrapping_shr(11);
+            y ^= y.wrapping_shl(7) & 0x9D2C5680;
+            y ^= y.wrapping_shr(15) & 0xEFC60000;
+            y ^= y.wrapping_shl(18);
+            self.mt_tempered[i] = y;
+        }
+
+        self.index = 0;
+    }
+}
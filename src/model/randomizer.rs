@@ -0,0 +1,381 @@
+use super::{piece::PieceClass, random::MersenneTwister};
+
+/// Decides the order pieces are handed out in
+pub trait Randomizer {
+    fn next_piece_class(&mut self, rng: &mut MersenneTwister) -> PieceClass;
+}
+
+/// The randomizers that can be picked in settings and stored in replays
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RandomizerKind {
+    SevenBag,
+    Nes,
+    Uniform,
+    FourteenBag,
+    Tgm,
+}
+
+/// Every piece once, in a shuffled order, before any piece repeats
+#[derive(Default)]
+pub struct SevenBag {
+    bag: Vec<PieceClass>,
+}
+
+/// Two of every piece, shuffled
+#[derive(Default)]
+pub struct FourteenBag {
+    bag: Vec<PieceClass>,
+}
+
+/// NES Tetris: a roll that repeats the last piece, or lands on the unused
+/// eighth value, is rerolled once
+#[derive(Default)]
+pub struct NesRandomizer {
+    previous: Option<PieceClass>,
+}
+
+/// Each piece equally likely every time
+pub struct Uniform;
+
+/// The Tetris: The Grand Master randomizer, which rerolls up to 6 times to
+/// avoid the last 4 pieces and never starts with an S, Z or O
+pub struct Tgm {
+    history: [PieceClass; 4],
+    first: bool,
+}
+
+/// Repeats a fixed sequence of pieces, for tests and puzzles
+pub struct FixedSequence {
+    sequence: Vec<PieceClass>,
+    index: usize,
+}
+
+const TGM_ROLLS: usize = 6;
+
+// 7!, the number of orders a bag can come out in
+const PERMUTATIONS: u32 = 5040;
+
+fn random_class(rng: &mut MersenneTwister) -> PieceClass {
+    PieceClass::ALL[rng.next_below(7) as usize]
+}
+
+impl RandomizerKind {
+    pub const ALL: [RandomizerKind; 5] = [
+        RandomizerKind::SevenBag,
+        RandomizerKind::Nes,
+        RandomizerKind::Uniform,
+        RandomizerKind::FourteenBag,
+        RandomizerKind::Tgm,
+    ];
+
+    /// The name used for this randomizer in files
+    pub fn name(self) -> &'static str {
+        match self {
+            RandomizerKind::SevenBag => "seven_bag",
+            RandomizerKind::Nes => "nes",
+            RandomizerKind::Uniform => "uniform",
+            RandomizerKind::FourteenBag => "fourteen_bag",
+            RandomizerKind::Tgm => "tgm",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<RandomizerKind> {
+        RandomizerKind::ALL
+            .into_iter()
+            .find(|kind| kind.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn create(self) -> Box<dyn Randomizer> {
+        match self {
+            RandomizerKind::SevenBag => Box::new(SevenBag::default()),
+            RandomizerKind::Nes => Box::new(NesRandomizer::default()),
+            RandomizerKind::Uniform => Box::new(Uniform),
+            RandomizerKind::FourteenBag => Box::new(FourteenBag::default()),
+            RandomizerKind::Tgm => Box::new(Tgm::new()),
+        }
+    }
+}
+
+impl Randomizer for SevenBag {
+    fn next_piece_class(&mut self, rng: &mut MersenneTwister) -> PieceClass {
+        if self.bag.is_empty() {
+            self.generate_permuation(rng);
+        }
+
+        self.bag.pop().unwrap()
+    }
+}
+
+impl SevenBag {
+    fn generate_permuation(&mut self, rng: &mut MersenneTwister) {
+        self.bag
+            .extend(decode_permutation(rng.next_below(PERMUTATIONS)));
+    }
+}
+
+/// Turns `value`, from 0 to 7! - 1, into one of the 7! orders of the pieces
+///
+/// `value` is read as a number in the factorial number system. Its most
+/// significant digit, worth 6!, picks one of the 7 remaining pieces, the next,
+/// worth 5!, one of the 6 left after that, and so on down to 1!.
+fn decode_permutation(value: u32) -> [PieceClass; 7] {
+    assert!(value < PERMUTATIONS);
+
+    let mut remaining_pieces = vec![
+        PieceClass::I,
+        PieceClass::O,
+        PieceClass::T,
+        PieceClass::S,
+        PieceClass::Z,
+        PieceClass::J,
+        PieceClass::L,
+    ];
+
+    let mut permutation = [PieceClass::I; 7];
+    let mut value = value;
+    let mut place_value = PERMUTATIONS;
+    for (i, piece) in permutation.iter_mut().enumerate() {
+        place_value /= 7 - i as u32;
+        let index = value / place_value;
+        value %= place_value;
+
+        *piece = remaining_pieces.remove(index as usize);
+    }
+
+    permutation
+}
+
+impl Randomizer for FourteenBag {
+    fn next_piece_class(&mut self, rng: &mut MersenneTwister) -> PieceClass {
+        if self.bag.is_empty() {
+            self.bag.extend(PieceClass::ALL);
+            self.bag.extend(PieceClass::ALL);
+
+            // Fisher-Yates shuffle
+            for i in (1..self.bag.len()).rev() {
+                let j = rng.next_below(i as u32 + 1) as usize;
+                self.bag.swap(i, j);
+            }
+        }
+
+        self.bag.pop().unwrap()
+    }
+}
+
+impl Randomizer for NesRandomizer {
+    fn next_piece_class(&mut self, rng: &mut MersenneTwister) -> PieceClass {
+        let previous = self.previous.map(|class| class as usize).unwrap_or(0);
+
+        let roll = (rng.next_number() % 8) as usize;
+        let class = if roll == 7 || Some(PieceClass::ALL[roll]) == self.previous {
+            PieceClass::ALL[((rng.next_number() % 8) as usize + previous) % 7]
+        } else {
+            PieceClass::ALL[roll]
+        };
+
+        self.previous = Some(class);
+        class
+    }
+}
+
+impl Randomizer for Uniform {
+    fn next_piece_class(&mut self, rng: &mut MersenneTwister) -> PieceClass {
+        random_class(rng)
+    }
+}
+
+impl Tgm {
+    pub fn new() -> Self {
+        Tgm {
+            history: [PieceClass::Z; 4],
+            first: true,
+        }
+    }
+}
+
+impl Default for Tgm {
+    fn default() -> Self {
+        Tgm::new()
+    }
+}
+
+impl Randomizer for Tgm {
+    fn next_piece_class(&mut self, rng: &mut MersenneTwister) -> PieceClass {
+        let class = if self.first {
+            self.first = false;
+
+            const FIRST_PIECES: [PieceClass; 4] =
+                [PieceClass::I, PieceClass::J, PieceClass::L, PieceClass::T];
+            FIRST_PIECES[rng.next_below(4) as usize]
+        } else {
+            let mut class = random_class(rng);
+            for _ in 1..TGM_ROLLS {
+                if !self.history.contains(&class) {
+                    break;
+                }
+                class = random_class(rng);
+            }
+            class
+        };
+
+        self.history.rotate_right(1);
+        self.history[0] = class;
+        class
+    }
+}
+
+impl FixedSequence {
+    pub fn new(sequence: Vec<PieceClass>) -> Self {
+        assert!(!sequence.is_empty());
+        FixedSequence { sequence, index: 0 }
+    }
+}
+
+impl Randomizer for FixedSequence {
+    fn next_piece_class(&mut self, _: &mut MersenneTwister) -> PieceClass {
+        let class = self.sequence[self.index];
+        self.index = (self.index + 1) % self.sequence.len();
+        class
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEEDS: u32 = 7000;
+
+    fn first_bag(seed: u32) -> Vec<PieceClass> {
+        let mut rng = MersenneTwister::new(seed);
+        let mut bag = SevenBag::default();
+        (0..7).map(|_| bag.next_piece_class(&mut rng)).collect()
+    }
+
+    fn is_permutation(pieces: &[PieceClass]) -> bool {
+        pieces.len() == 7 && PieceClass::ALL.iter().all(|class| pieces.contains(class))
+    }
+
+    #[test]
+    fn decode_covers_every_permutation_once() {
+        let mut seen = std::collections::HashSet::new();
+        for value in 0..PERMUTATIONS {
+            let permutation = decode_permutation(value);
+            assert!(is_permutation(&permutation), "{} gave {:?}", value, permutation);
+            assert!(
+                seen.insert(permutation.map(|class| class as usize)),
+                "{} repeated {:?}",
+                value,
+                permutation
+            );
+        }
+
+        assert_eq!(seen.len(), PERMUTATIONS as usize);
+    }
+
+    #[test]
+    fn decode_reads_factorial_digits() {
+        use PieceClass::*;
+
+        assert_eq!(decode_permutation(0), [I, O, T, S, Z, J, L]);
+        assert_eq!(decode_permutation(1), [I, O, T, S, Z, L, J]);
+        assert_eq!(decode_permutation(720), [O, I, T, S, Z, J, L]);
+        assert_eq!(decode_permutation(PERMUTATIONS - 1), [L, J, Z, S, T, O, I]);
+    }
+
+    #[test]
+    fn every_bag_is_a_permutation() {
+        for seed in 0..100 {
+            let mut rng = MersenneTwister::new(seed);
+            let mut bag = SevenBag::default();
+            for _ in 0..50 {
+                let pieces: Vec<_> = (0..7).map(|_| bag.next_piece_class(&mut rng)).collect();
+                assert!(is_permutation(&pieces), "seed {} gave {:?}", seed, pieces);
+            }
+        }
+    }
+
+    #[test]
+    fn pieces_spread_evenly_over_bag_positions() {
+        // Each piece should land in each position of the first bag about
+        // SEEDS / 7 times. The standard deviation is about 29, so this allows
+        // for roughly five of them either way.
+        let mut counts = [[0u32; 7]; 7];
+        for seed in 0..SEEDS {
+            for (position, class) in first_bag(seed).into_iter().enumerate() {
+                counts[class as usize][position] += 1;
+            }
+        }
+
+        let expected = SEEDS / 7;
+        for (class, positions) in counts.iter().enumerate() {
+            for (position, count) in positions.iter().enumerate() {
+                assert!(
+                    count.abs_diff(expected) < 150,
+                    "{:?} was at position {} {} times, expected about {}",
+                    PieceClass::ALL[class],
+                    position,
+                    count,
+                    expected
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn next_below_stays_in_range() {
+        let mut rng = MersenneTwister::new(1);
+        let mut counts = [0u32; 7];
+        for _ in 0..70000 {
+            counts[rng.next_below(7) as usize] += 1;
+        }
+
+        for count in counts {
+            assert!(count.abs_diff(10000) < 500, "{:?}", counts);
+        }
+    }
+
+    #[test]
+    fn names_ignore_case() {
+        for kind in RandomizerKind::ALL {
+            assert_eq!(RandomizerKind::from_name(kind.name()), Some(kind));
+            let upper = kind.name().to_ascii_uppercase();
+            assert_eq!(RandomizerKind::from_name(&upper), Some(kind));
+        }
+        assert_eq!(
+            RandomizerKind::from_name("Seven_Bag"),
+            Some(RandomizerKind::SevenBag)
+        );
+        assert_eq!(RandomizerKind::from_name("sevenbag"), None);
+    }
+
+    #[test]
+    fn fourteen_bag_deals_each_piece_twice() {
+        let mut rng = MersenneTwister::new(3);
+        let mut bag = FourteenBag::default();
+        for _ in 0..20 {
+            let mut counts = [0; 7];
+            for _ in 0..14 {
+                counts[bag.next_piece_class(&mut rng) as usize] += 1;
+            }
+            assert_eq!(counts, [2; 7]);
+        }
+    }
+
+    #[test]
+    fn tgm_never_starts_with_s_z_or_o() {
+        for seed in 0..1000 {
+            let class = Tgm::new().next_piece_class(&mut MersenneTwister::new(seed));
+            assert!(![PieceClass::S, PieceClass::Z, PieceClass::O].contains(&class));
+        }
+    }
+
+    #[test]
+    fn fixed_sequence_repeats() {
+        use PieceClass::*;
+
+        let mut rng = MersenneTwister::new(0);
+        let mut fixed = FixedSequence::new(vec![T, I, O]);
+        let pieces: Vec<_> = (0..7).map(|_| fixed.next_piece_class(&mut rng)).collect();
+        assert_eq!(pieces, [T, I, O, T, I, O, T]);
+    }
+}
@@ -0,0 +1,199 @@
+use super::{
+    board::Board,
+    piece::{Piece, PieceClass},
+};
+
+/// Decides where a piece ends up when the player rotates it
+pub trait RotationSystem {
+    /// Returns `piece` turned a quarter turn, or `None` if it can't turn
+    fn rotate(&self, piece: &Piece, board: &Board, clockwise: bool) -> Option<Piece>;
+}
+
+/// The NES rules: turn in place or not at all
+pub struct NesRotation;
+
+/// The guideline Super Rotation System, which tries a list of offsets
+/// ("kicks") until one fits
+pub struct SuperRotation;
+
+// Kicks for each orientation and direction, indexed by
+// `orientation * 2 + counterclockwise`. As in the guideline tables, positive y
+// is up, so it is negated before being applied to the board.
+const JLSTZ_KICKS: [[(isize, isize); 5]; 8] = [
+    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)], // 0 -> R
+    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],    // 0 -> L
+    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],     // R -> 2
+    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],     // R -> 0
+    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],    // 2 -> L
+    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)], // 2 -> R
+    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],  // L -> 0
+    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],  // L -> 2
+];
+
+const I_KICKS: [[(isize, isize); 5]; 8] = [
+    [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)], // 0 -> R
+    [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)], // 0 -> L
+    [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)], // R -> 2
+    [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)], // R -> 0
+    [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)], // 2 -> L
+    [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)], // 2 -> R
+    [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)], // L -> 0
+    [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)], // L -> 2
+];
+
+fn turned(piece: &Piece, clockwise: bool) -> Piece {
+    let mut piece = piece.clone();
+    if clockwise {
+        piece.rotate_right();
+    } else {
+        piece.rotate_left();
+    }
+    piece
+}
+
+impl RotationSystem for NesRotation {
+    fn rotate(&self, piece: &Piece, board: &Board, clockwise: bool) -> Option<Piece> {
+        let piece = turned(piece, clockwise);
+        if board.verify(&piece) {
+            Some(piece)
+        } else {
+            None
+        }
+    }
+}
+
+impl RotationSystem for SuperRotation {
+    fn rotate(&self, piece: &Piece, board: &Board, clockwise: bool) -> Option<Piece> {
+        let kicks = match piece.class() {
+            PieceClass::O => return Some(turned(piece, clockwise)),
+            PieceClass::I => &I_KICKS,
+            _ => &JLSTZ_KICKS,
+        };
+
+        let turned = turned(piece, clockwise);
+        let index = piece.orientation() * 2 + if clockwise { 0 } else { 1 };
+
+        kicks[index].iter().find_map(|(x, y)| {
+            let mut kicked = turned.clone();
+            kicked.translate(*x, -*y);
+            if board.verify(&kicked) {
+                Some(kicked)
+            } else {
+                None
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{
+        board::{BOARD_HEIGHT, BOARD_WIDTH},
+        tile::TileColor,
+    };
+
+    const BOTTOM: isize = BOARD_HEIGHT as isize - 1;
+
+    /// `class` in its spawn orientation, moved so its position is `(x, y)`
+    fn piece_at(class: PieceClass, x: isize, y: isize) -> Piece {
+        let mut piece = Piece::new(class);
+        let (from_x, from_y) = piece.position();
+        piece.translate(x - from_x, y - from_y);
+        piece
+    }
+
+    fn cells(piece: &Piece) -> Vec<(isize, isize)> {
+        (0..4).map(|i| piece.get_tile_position(i)).collect()
+    }
+
+    fn offset(from: &Piece, to: &Piece) -> (isize, isize) {
+        (
+            to.position().0 - from.position().0,
+            to.position().1 - from.position().1,
+        )
+    }
+
+    #[test]
+    fn t_kicks_off_the_left_wall() {
+        // Flat on the floor against the wall, so turning in place would put
+        // the stem through the floor
+        let board = Board::new();
+        let t = piece_at(PieceClass::T, 1, BOTTOM);
+        assert!(board.verify(&t));
+        assert!(!board.verify(&turned(&t, true)));
+
+        // 0 -> R: (-1, 0) puts the stem in the floor too, so the third kick,
+        // one left and one up, is used
+        let rotated = SuperRotation.rotate(&t, &board, true).unwrap();
+        assert_eq!(rotated.orientation(), 1);
+        assert_eq!(offset(&t, &rotated), (-1, -1));
+
+        // Pointing right against the wall, turning back kicks away from it
+        let mut upright = piece_at(PieceClass::T, 0, 10);
+        upright.rotate_right();
+        assert!(board.verify(&upright));
+        let rotated = SuperRotation.rotate(&upright, &board, false).unwrap();
+        assert_eq!(rotated.orientation(), 0);
+        assert_eq!(offset(&upright, &rotated), (1, 0));
+    }
+
+    #[test]
+    fn i_uses_its_own_kicks() {
+        let mut board = Board::new();
+        let i = piece_at(PieceClass::I, 5, 10);
+        let in_place = turned(&i, true);
+
+        // Block the column it would turn into. The JLSTZ table would try one
+        // column left next, the I table tries two.
+        let (x, y) = in_place.get_tile_position(0);
+        board.set(x, y, Some(TileColor::Red));
+
+        let rotated = SuperRotation.rotate(&i, &board, true).unwrap();
+        assert_eq!(offset(&in_place, &rotated), (-2, 0));
+        assert_eq!(rotated.orientation(), 1);
+    }
+
+    #[test]
+    fn o_turns_in_place() {
+        let board = Board::new();
+        let o = piece_at(PieceClass::O, 4, BOTTOM);
+        let rotated = SuperRotation.rotate(&o, &board, true).unwrap();
+        assert_eq!(offset(&o, &rotated), (0, 0));
+    }
+
+    #[test]
+    fn fails_when_every_kick_is_blocked() {
+        // Everything but the piece itself is filled
+        let t = piece_at(PieceClass::T, 4, 10);
+        let mut board = Board::new();
+        for y in 0..BOARD_HEIGHT as isize {
+            for x in 0..BOARD_WIDTH as isize {
+                if !cells(&t).contains(&(x, y)) {
+                    board.set(x, y, Some(TileColor::Red));
+                }
+            }
+        }
+
+        for clockwise in [true, false] {
+            assert!(SuperRotation.rotate(&t, &board, clockwise).is_none());
+            assert!(NesRotation.rotate(&t, &board, clockwise).is_none());
+        }
+    }
+
+    #[test]
+    fn nes_rotation_never_kicks() {
+        let board = Board::new();
+
+        // Where SRS would kick off the wall, the NES doesn't turn at all
+        let t = piece_at(PieceClass::T, 1, BOTTOM);
+        assert!(SuperRotation.rotate(&t, &board, true).is_some());
+        assert!(NesRotation.rotate(&t, &board, true).is_none());
+
+        // With room it turns in place
+        let t = piece_at(PieceClass::T, 4, 10);
+        let rotated = NesRotation.rotate(&t, &board, true).unwrap();
+        assert_eq!(offset(&t, &rotated), (0, 0));
+        assert_eq!(cells(&rotated), cells(&turned(&t, true)));
+    }
+}
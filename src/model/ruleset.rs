@@ -0,0 +1,58 @@
+use super::{
+    rotation::{NesRotation, RotationSystem, SuperRotation},
+    scoring::{GuidelineScoring, NesScoring, ScoringSystem},
+};
+
+/// A family of rules a game is played under
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ruleset {
+    /// As close to NES Tetris as we can make it
+    Classic,
+    /// Guideline-style rules
+    Modern,
+}
+
+impl Ruleset {
+    pub const ALL: [Ruleset; 2] = [Ruleset::Classic, Ruleset::Modern];
+
+    /// The name used for this ruleset in files and on screen
+    pub fn name(self) -> &'static str {
+        match self {
+            Ruleset::Classic => "classic",
+            Ruleset::Modern => "modern",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Ruleset> {
+        Ruleset::ALL
+            .into_iter()
+            .find(|ruleset| ruleset.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn hard_drop(self) -> bool {
+        self == Ruleset::Modern
+    }
+
+    pub fn ghost_piece(self) -> bool {
+        self == Ruleset::Modern
+    }
+
+    pub fn hold(self) -> bool {
+        self == Ruleset::Modern
+    }
+
+    pub fn rotation_system(self) -> &'static dyn RotationSystem {
+        match self {
+            Ruleset::Classic => &NesRotation,
+            Ruleset::Modern => &SuperRotation,
+        }
+    }
+
+    /// Scoring starts fresh for every game, as it tracks combos
+    pub fn scoring_system(self) -> Box<dyn ScoringSystem> {
+        match self {
+            Ruleset::Classic => Box::new(NesScoring),
+            Ruleset::Modern => Box::new(GuidelineScoring::default()),
+        }
+    }
+}
@@ -0,0 +1,346 @@
+use super::{
+    board::Board,
+    piece::{Piece, PieceClass},
+};
+
+/// Whether a locked T piece was spun into place
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TSpin {
+    None,
+    /// Only one of the corners the T points at is filled
+    Mini,
+    Full,
+}
+
+/// What a piece locking did, as seen by the scoring rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lock {
+    pub lines: usize,
+    pub t_spin: TSpin,
+    /// The board was left empty
+    pub perfect_clear: bool,
+}
+
+/// A scored lock, for the view to announce
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Clear {
+    pub lines: usize,
+    pub t_spin: TSpin,
+    /// Clears in a row before this one, 0 for the first
+    pub combo: usize,
+    /// Followed another tetris or T-spin clear with no easier clear between
+    pub back_to_back: bool,
+    pub perfect_clear: bool,
+    pub points: usize,
+}
+
+/// Decides what each piece locking is worth
+pub trait ScoringSystem {
+    /// Scores `lock` at `level`, keeping track of any combo or back-to-back
+    /// chain it continues or breaks
+    fn score(&mut self, lock: Lock, level: usize) -> Clear;
+}
+
+/// The NES rules: points for the number of lines and nothing else
+pub struct NesScoring;
+
+/// Guideline scoring, with T-spins, combos, back-to-back bonuses and perfect
+/// clears
+#[derive(Default)]
+pub struct GuidelineScoring {
+    // The clears in a row so far, `None` once a piece locks without clearing
+    combo: Option<usize>,
+    back_to_back: bool,
+}
+
+const NES_POINTS: [usize; 5] = [0, 40, 100, 300, 1200];
+
+const COMBO_POINTS: usize = 50;
+
+// Perfect clear bonuses by lines, and for a back-to-back tetris
+const PERFECT_CLEAR_POINTS: [usize; 5] = [0, 800, 1200, 1800, 2000];
+const BACK_TO_BACK_PERFECT_TETRIS_POINTS: usize = 3200;
+
+// The corners of the T's bounding box, and for each orientation the two
+// corners on the side it points to
+const T_CORNERS: [(isize, isize); 4] = [(-1, -1), (1, -1), (1, 1), (-1, 1)];
+const T_FRONT_CORNERS: [[usize; 2]; 4] = [[0, 1], [1, 2], [2, 3], [3, 0]];
+
+/// Checks a T piece about to lock with the three-corner rule, where
+/// `last_kick` is the offset of the last rotation if that was the last thing
+/// to move the piece
+pub fn t_spin(piece: &Piece, board: &Board, last_kick: Option<(isize, isize)>) -> TSpin {
+    let kick = match last_kick {
+        Some(kick) if piece.class() == PieceClass::T => kick,
+        _ => return TSpin::None,
+    };
+
+    let (x, y) = piece.position();
+    let filled: Vec<bool> = T_CORNERS
+        .iter()
+        .map(|(dx, dy)| board.get(x + dx, y + dy).map_or(true, |tile| tile.is_some()))
+        .collect();
+
+    if filled.iter().filter(|filled| **filled).count() < 3 {
+        return TSpin::None;
+    }
+
+    // The last kick in the table moves the piece a column and two rows,
+    // which always counts as a full T-spin
+    let front = T_FRONT_CORNERS[piece.orientation()];
+    if front.iter().all(|corner| filled[*corner]) || (kick.0.abs() == 1 && kick.1.abs() == 2) {
+        TSpin::Full
+    } else {
+        TSpin::Mini
+    }
+}
+
+impl ScoringSystem for NesScoring {
+    fn score(&mut self, lock: Lock, level: usize) -> Clear {
+        Clear {
+            lines: lock.lines,
+            t_spin: TSpin::None,
+            combo: 0,
+            back_to_back: false,
+            perfect_clear: lock.perfect_clear,
+            points: (level + 1) * NES_POINTS[lock.lines.min(4)],
+        }
+    }
+}
+
+impl ScoringSystem for GuidelineScoring {
+    fn score(&mut self, lock: Lock, level: usize) -> Clear {
+        let lines = lock.lines.min(4);
+        let mut points = match (lock.t_spin, lines) {
+            (TSpin::None, _) => [0, 100, 300, 500, 800][lines],
+            (TSpin::Mini, _) => [100, 200, 400, 400, 400][lines],
+            (TSpin::Full, _) => [400, 800, 1200, 1600, 1600][lines],
+        };
+
+        // Locking without clearing ends a combo but not a back-to-back chain
+        let mut back_to_back = false;
+        let mut combo = 0;
+        if lines == 0 {
+            self.combo = None;
+        } else {
+            let difficult = lines == 4 || lock.t_spin != TSpin::None;
+            back_to_back = difficult && self.back_to_back;
+            self.back_to_back = difficult;
+            if back_to_back {
+                points = points * 3 / 2;
+            }
+
+            combo = self.combo.map_or(0, |combo| combo + 1);
+            self.combo = Some(combo);
+            points += COMBO_POINTS * combo;
+
+            if lock.perfect_clear {
+                points += if back_to_back && lines == 4 {
+                    BACK_TO_BACK_PERFECT_TETRIS_POINTS
+                } else {
+                    PERFECT_CLEAR_POINTS[lines]
+                };
+            }
+        }
+
+        Clear {
+            lines: lock.lines,
+            t_spin: lock.t_spin,
+            combo,
+            back_to_back,
+            perfect_clear: lock.perfect_clear,
+            points: (level + 1) * points,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{board::BOARD_HEIGHT, tile::TileColor};
+
+    /// Builds a board whose bottom rows are drawn by `rows`, top first, with
+    /// `#` for a filled tile
+    fn board(rows: &[&str]) -> Board {
+        let mut board = Board::new();
+        let top = BOARD_HEIGHT as isize - rows.len() as isize;
+        for (y, row) in rows.iter().enumerate() {
+            for (x, tile) in row.chars().enumerate() {
+                if tile == '#' {
+                    board.set(x as isize, top + y as isize, Some(TileColor::Red));
+                }
+            }
+        }
+        board
+    }
+
+    /// A T centred on `(x, y)` after `turns` clockwise rotations from
+    /// pointing up
+    fn t_piece(turns: usize, x: isize, y: isize) -> Piece {
+        let mut piece = Piece::new(PieceClass::T);
+        for _ in 0..turns {
+            piece.rotate_right();
+        }
+        let (from_x, from_y) = piece.position();
+        piece.translate(x - from_x, y - from_y);
+        piece
+    }
+
+    fn lock(lines: usize, t_spin: TSpin) -> Lock {
+        Lock {
+            lines,
+            t_spin,
+            perfect_clear: false,
+        }
+    }
+
+    const BOTTOM: isize = BOARD_HEIGHT as isize - 1;
+
+    #[test]
+    fn three_corners_make_a_t_spin() {
+        // A T-spin double slot with the T pointing down into it
+        let mut slot = board(&["####......", "###...####", "####.#####"]);
+        let t = t_piece(2, 4, BOTTOM - 1);
+        assert!(slot.verify(&t));
+        assert_eq!(t_spin(&t, &slot, Some((0, 0))), TSpin::Full);
+
+        // Only a rotation counts, and only for a T
+        assert_eq!(t_spin(&t, &slot, None), TSpin::None);
+        let mut j = Piece::new(PieceClass::J);
+        j.translate(
+            t.position().0 - j.position().0,
+            t.position().1 - j.position().1,
+        );
+        assert_eq!(t_spin(&j, &slot, Some((0, 0))), TSpin::None);
+
+        // Two corners aren't enough
+        slot.set(3, BOTTOM - 2, None);
+        assert_eq!(t_spin(&t, &slot, Some((0, 0))), TSpin::None);
+    }
+
+    #[test]
+    fn walls_and_floor_count_as_corners() {
+        // Pointing up on the floor, with one front corner filled
+        let t = t_piece(0, 4, BOTTOM);
+        let floor = board(&["...#......", ".........."]);
+        assert!(floor.verify(&t));
+        assert_eq!(t_spin(&t, &floor, Some((0, 0))), TSpin::Mini);
+
+        // Pointing right against the left wall
+        let t = t_piece(1, 0, BOTTOM - 1);
+        let mut wall = board(&["..........", "..........", ".#........"]);
+        assert!(wall.verify(&t));
+        assert_eq!(t_spin(&t, &wall, Some((0, 0))), TSpin::Mini);
+        wall.set(1, BOTTOM - 2, Some(TileColor::Red));
+        assert_eq!(t_spin(&t, &wall, Some((0, 0))), TSpin::Full);
+    }
+
+    #[test]
+    fn front_corners_decide_mini_or_full() {
+        // Pointing up with both back corners filled and one front corner
+        let mut board = board(&["...#......", "..........", "####.#####"]);
+        let t = t_piece(0, 4, BOTTOM - 1);
+        assert!(board.verify(&t));
+        assert_eq!(t_spin(&t, &board, Some((0, 0))), TSpin::Mini);
+
+        board.set(5, BOTTOM - 2, Some(TileColor::Red));
+        assert_eq!(t_spin(&t, &board, Some((0, 0))), TSpin::Full);
+
+        // Both front corners and one back corner is still full
+        board.set(3, BOTTOM, None);
+        assert_eq!(t_spin(&t, &board, Some((0, 0))), TSpin::Full);
+    }
+
+    #[test]
+    fn last_kick_upgrades_a_mini() {
+        let board = board(&["...#......", "..........", "####.#####"]);
+        let t = t_piece(0, 4, BOTTOM - 1);
+
+        for kick in [(1, 2), (-1, 2), (1, -2), (-1, -2)] {
+            assert_eq!(t_spin(&t, &board, Some(kick)), TSpin::Full, "{:?}", kick);
+        }
+        for kick in [(1, 0), (-1, -1), (0, 2), (2, 1)] {
+            assert_eq!(t_spin(&t, &board, Some(kick)), TSpin::Mini, "{:?}", kick);
+        }
+    }
+
+    #[test]
+    fn combos_count_up_until_a_lock_clears_nothing() {
+        let mut scoring = GuidelineScoring::default();
+
+        let combos: Vec<_> = (0..3)
+            .map(|_| scoring.score(lock(1, TSpin::None), 0))
+            .map(|clear| (clear.combo, clear.points))
+            .collect();
+        assert_eq!(combos, [(0, 100), (1, 150), (2, 200)]);
+
+        let miss = scoring.score(lock(0, TSpin::None), 0);
+        assert_eq!((miss.combo, miss.points), (0, 0));
+
+        let clear = scoring.score(lock(2, TSpin::None), 0);
+        assert_eq!((clear.combo, clear.points), (0, 300));
+
+        // The combo bonus scales with the level like everything else
+        let clear = scoring.score(lock(1, TSpin::None), 2);
+        assert_eq!((clear.combo, clear.points), (1, 3 * 150));
+    }
+
+    #[test]
+    fn back_to_back_pays_half_again() {
+        let mut scoring = GuidelineScoring::default();
+        let mut score = |lines, t_spin| {
+            // Locking a piece without clearing between each clear keeps
+            // combo points out of the way, and doesn't break the chain
+            let clear = scoring.score(lock(lines, t_spin), 0);
+            scoring.score(lock(0, TSpin::None), 0);
+            (clear.back_to_back, clear.points)
+        };
+
+        assert_eq!(score(4, TSpin::None), (false, 800));
+        assert_eq!(score(4, TSpin::None), (true, 1200));
+        assert_eq!(score(1, TSpin::Full), (true, 1200));
+        assert_eq!(score(1, TSpin::Mini), (true, 300));
+        // A T-spin that clears nothing neither continues nor breaks it
+        assert_eq!(score(0, TSpin::Full), (false, 400));
+        assert_eq!(score(2, TSpin::Full), (true, 1800));
+
+        // Any easier clear breaks it
+        for lines in 1..=3 {
+            assert!(!score(lines, TSpin::None).0);
+            assert_eq!(score(4, TSpin::None), (false, 800));
+        }
+    }
+
+    #[test]
+    fn perfect_clears_add_a_bonus() {
+        let mut scoring = GuidelineScoring::default();
+        let mut score = |lines| {
+            let clear = scoring.score(
+                Lock {
+                    lines,
+                    t_spin: TSpin::None,
+                    perfect_clear: true,
+                },
+                0,
+            );
+            scoring.score(lock(0, TSpin::None), 0);
+            (clear.perfect_clear, clear.points)
+        };
+
+        assert_eq!(score(1), (true, 100 + 800));
+        assert_eq!(score(2), (true, 300 + 1200));
+        assert_eq!(score(3), (true, 500 + 1800));
+        assert_eq!(score(4), (true, 800 + 2000));
+        // A back-to-back tetris gets the bigger bonus instead
+        assert_eq!(score(4), (true, 1200 + 3200));
+    }
+
+    #[test]
+    fn nes_scoring_only_counts_lines() {
+        let mut scoring = NesScoring;
+        let clear = scoring.score(lock(4, TSpin::Full), 9);
+        assert_eq!((clear.t_spin, clear.points), (TSpin::None, 10 * 1200));
+        assert_eq!(scoring.score(lock(4, TSpin::None), 0).combo, 0);
+        assert_eq!(scoring.score(lock(2, TSpin::None), 0).points, 100);
+    }
+}
@@ -0,0 +1,321 @@
+use super::{
+    event::Event,
+    game::{Game, ARE},
+    input::{Action, InputFrame},
+};
+
+/// How long a direction must be held before it repeats, in frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DasSettings {
+    /// Frames from the first shift to the first repeat
+    pub initial_delay: u8,
+    /// Frames between repeats after that
+    pub repeat_delay: u8,
+}
+
+/// Delayed auto shift charge for one direction, counted up as on the NES
+///
+/// The charge is only reset by a fresh press, so it carries over while no
+/// piece is in play, and a shift blocked by a wall charges it fully.
+#[derive(Default)]
+struct Das(u8);
+
+/// Runs a game one 60 Hz frame at a time from the buttons held on each frame
+pub struct Simulation {
+    game: Game,
+
+    drop_counter: u8,
+    das: DasSettings,
+    das_left: Das,
+    das_right: Das,
+    das_down: Das,
+    previous_input: InputFrame,
+
+    are: ARE,
+
+    frame_counter: usize,
+}
+
+impl Simulation {
+    pub fn new(game: Game, das: DasSettings) -> Self {
+        Simulation {
+            drop_counter: game.drop_time(),
+            game,
+            das,
+            das_left: Das::default(),
+            das_right: Das::default(),
+            das_down: Das::default(),
+            previous_input: InputFrame::default(),
+            are: ARE::None,
+            frame_counter: 0,
+        }
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// Takes the game's events since the last call, oldest first
+    pub fn take_events(&mut self) -> Vec<Event> {
+        self.game.take_events()
+    }
+
+    /// Pushes garbage onto the board, returning true if that ends the game.
+    /// See [`Game::add_garbage`].
+    pub fn add_garbage(&mut self, rows: usize) -> bool {
+        self.game.add_garbage(rows)
+    }
+
+    pub fn das(&self) -> DasSettings {
+        self.das
+    }
+
+    pub fn frame(&self) -> usize {
+        self.frame_counter
+    }
+
+    /// Advances one frame, returning true once the game has ended
+    pub fn step(&mut self, input: InputFrame) -> bool {
+        // Update ARE & frame counter
+        self.frame_counter += 1;
+        if let ARE::ARE(value) = &mut self.are {
+            *value -= 1;
+        }
+
+        // Timed modes end once the clock has run out
+        if self.game.is_complete() {
+            return true;
+        }
+        self.game.tick();
+
+        // Read input & update game. Shifting, rotating and dropping are
+        // independent, so a piece can rotate on the same frame it shifts.
+        // Like the NES, held directions are ignored while no piece is in
+        // play, so their charge carries over to the next piece.
+        let previous = self.previous_input;
+        self.previous_input = input;
+
+        if self.game.current_piece().is_some() {
+            let left = input.is_held(Action::MoveLeft);
+            let right = input.is_held(Action::MoveRight);
+
+            if left && !right {
+                let pressed = input.just_pressed(previous, Action::MoveLeft);
+                if self.das_left.add_key_frame(pressed, self.das) && !self.game.move_left() {
+                    self.das_left.charge(self.das);
+                }
+            }
+
+            if right && !left {
+                let pressed = input.just_pressed(previous, Action::MoveRight);
+                if self.das_right.add_key_frame(pressed, self.das) && !self.game.move_right() {
+                    self.das_right.charge(self.das);
+                }
+            }
+        }
+
+        if input.just_pressed(previous, Action::RotateCounterclockwise) {
+            self.game.rotate_left();
+        }
+
+        if input.just_pressed(previous, Action::RotateClockwise) {
+            self.game.rotate_right();
+        }
+
+        if input.just_pressed(previous, Action::Hold) && self.game.hold() {
+            return true;
+        }
+
+        if input.just_pressed(previous, Action::HardDrop) {
+            if let Some(are) = self.game.hard_drop() {
+                if let ARE::None = self.are {
+                    self.are = are;
+                }
+            }
+        }
+
+        if input.is_held(Action::SoftDrop) {
+            let pressed = input.just_pressed(previous, Action::SoftDrop);
+            if self.das_down.add_key_frame(pressed, self.das) {
+                if let Some(are) = self.game.move_down(true) {
+                    if let ARE::None = self.are {
+                        self.are = are;
+                    }
+                }
+            }
+        }
+
+        if match &mut self.are {
+            ARE::ARE(step) => {
+                if *step == 0 {
+                    if self.game.finish_are() {
+                        return true;
+                    }
+                    true
+                } else {
+                    false
+                }
+            }
+            ARE::LineDelay(step, lines_cleared) => {
+                if self.frame_counter.is_multiple_of(4) {
+                    if *step < 5 {
+                        self.game.clear_animation(*step, lines_cleared);
+                        *step += 1;
+                        false
+                    } else {
+                        self.game.collapse(lines_cleared);
+                        if self.game.is_complete() || self.game.finish_are() {
+                            return true;
+                        }
+                        true
+                    }
+                } else {
+                    false
+                }
+            }
+            ARE::None => {
+                if self.drop_counter == 0 {
+                    self.drop_counter = self.game.drop_time();
+                    if let Some(are) = self.game.move_down(false) {
+                        self.are = are;
+                    }
+                } else {
+                    self.drop_counter -= 1;
+                }
+                false
+            }
+        } {
+            self.are = ARE::None;
+        }
+
+        false
+    }
+}
+
+impl Das {
+    /// Counts one frame of the direction being held, returning true when the
+    /// piece should shift
+    pub fn add_key_frame(&mut self, pressed: bool, settings: DasSettings) -> bool {
+        if pressed {
+            self.0 = 0;
+            return true;
+        }
+
+        // Saturating, as a full charge can already be at the largest delay
+        self.0 = self.0.saturating_add(1);
+        if self.0 >= settings.initial_delay {
+            self.0 = settings.initial_delay - settings.repeat_delay;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Charges fully, so the next held frame shifts
+    pub fn charge(&mut self, settings: DasSettings) {
+        self.0 = settings.initial_delay;
+    }
+}
+
+impl DasSettings {
+    /// Whether both delays are at least a frame and repeating isn't slower
+    /// than the initial delay
+    pub fn is_valid(self) -> bool {
+        self.repeat_delay > 0 && self.repeat_delay <= self.initial_delay
+    }
+}
+
+impl Default for DasSettings {
+    fn default() -> Self {
+        DasSettings {
+            initial_delay: 16,
+            repeat_delay: 6,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{
+        GameMode, PieceGenerator, RandomizerKind, Ruleset, BOARD_WIDTH, DEFAULT_DIG_ROWS,
+    };
+
+    fn new_simulation(das: DasSettings) -> Simulation {
+        let game = Game::new(
+            GameMode::Marathon,
+            Ruleset::Modern,
+            0,
+            DEFAULT_DIG_ROWS,
+            PieceGenerator::new(3, RandomizerKind::SevenBag, 1),
+        );
+        Simulation::new(game, das)
+    }
+
+    fn held(actions: &[Action]) -> InputFrame {
+        let mut input = InputFrame::default();
+        for action in actions {
+            input.hold(*action);
+        }
+        input
+    }
+
+    fn piece_x(simulation: &Simulation) -> isize {
+        simulation.game().current_piece().unwrap().position().0
+    }
+
+    #[test]
+    fn holding_into_a_wall_charges_fully() {
+        // The longest delay the config allows
+        let das = DasSettings {
+            initial_delay: 255,
+            repeat_delay: 6,
+        };
+        let mut simulation = new_simulation(das);
+
+        // Tap over to the wall
+        let mut x = piece_x(&simulation);
+        loop {
+            simulation.step(held(&[Action::MoveLeft]));
+            simulation.step(InputFrame::default());
+            if piece_x(&simulation) == x {
+                break;
+            }
+            x = piece_x(&simulation);
+        }
+
+        for _ in 0..10 {
+            assert!(!simulation.step(held(&[Action::MoveLeft])));
+            assert_eq!(simulation.das_left.0, das.initial_delay);
+            assert_eq!(piece_x(&simulation), x);
+        }
+    }
+
+    #[test]
+    fn charge_carries_over_to_the_next_piece() {
+        let das = DasSettings::default();
+        let mut simulation = new_simulation(das);
+        let left = held(&[Action::MoveLeft]);
+
+        // Slide into the wall, which leaves the charge full, and drop there
+        for _ in 0..BOARD_WIDTH * das.initial_delay as usize {
+            simulation.step(left);
+        }
+        simulation.step(held(&[Action::MoveLeft, Action::HardDrop]));
+        assert!(simulation.game().current_piece().is_none());
+        let charge = simulation.das_left.0;
+        assert_eq!(charge, das.initial_delay);
+
+        // Still holding left through the entry delay
+        while simulation.game().current_piece().is_none() {
+            assert!(!simulation.step(left));
+            assert_eq!(simulation.das_left.0, charge);
+        }
+
+        // The new piece shifts on its first frame rather than waiting out the
+        // initial delay again
+        let x = piece_x(&simulation);
+        simulation.step(left);
+        assert_eq!(piece_x(&simulation), x - 1);
+    }
+}
@@ -0,0 +1,173 @@
+use super::{
+    attack::AttackTable, event::Event, input::InputFrame, scoring::Clear, simulation::Simulation,
+};
+
+/// Two games played side by side, where clearing lines sends garbage to the
+/// other player
+pub struct VersusGame {
+    players: [Simulation; 2],
+    // Garbage sent to each player that hasn't reached their board yet
+    incoming: [usize; 2],
+    events: [Vec<Event>; 2],
+    attack: AttackTable,
+}
+
+/// How a round ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The player with this index outlasted the other
+    Won(usize),
+    /// Both players topped out on the same frame
+    Draw,
+}
+
+impl VersusGame {
+    /// Pits two simulations against each other. Both should be dealt from
+    /// the same seed for the round to be fair.
+    pub fn new(players: [Simulation; 2], attack: AttackTable) -> Self {
+        VersusGame {
+            players,
+            incoming: [0; 2],
+            events: [Vec::new(), Vec::new()],
+            attack,
+        }
+    }
+
+    pub fn player(&self, index: usize) -> &Simulation {
+        &self.players[index]
+    }
+
+    /// Garbage rows on their way to a player
+    pub fn incoming(&self, index: usize) -> usize {
+        self.incoming[index]
+    }
+
+    /// Takes a player's events since the last call, oldest first
+    pub fn take_events(&mut self, index: usize) -> Vec<Event> {
+        std::mem::take(&mut self.events[index])
+    }
+
+    /// Advances both games one frame, returning the outcome once either
+    /// player has lost
+    ///
+    /// Clears first cancel garbage on its way to the player who cleared,
+    /// and send the rest to the other player. Garbage lands when a piece
+    /// locks without clearing anything.
+    pub fn step(&mut self, inputs: [InputFrame; 2]) -> Option<Outcome> {
+        let mut lost = [false; 2];
+        for (player, input) in inputs.into_iter().enumerate() {
+            lost[player] = self.players[player].step(input);
+        }
+
+        for (player, lost) in lost.iter_mut().enumerate() {
+            let events = self.players[player].take_events();
+            for event in &events {
+                if let Event::Locked { rows, clear, .. } = event {
+                    *lost |= self.locked(player, rows, clear);
+                }
+            }
+
+            self.events[player].extend(events);
+            self.events[player].extend(self.players[player].take_events());
+        }
+
+        match lost {
+            [true, true] => Some(Outcome::Draw),
+            [true, false] => Some(Outcome::Won(1)),
+            [false, true] => Some(Outcome::Won(0)),
+            [false, false] => None,
+        }
+    }
+
+    // Sends or lands garbage for a piece `player` locked, filling `rows`.
+    // Returns true if the garbage topped them out.
+    fn locked(&mut self, player: usize, rows: &[isize], clear: &Clear) -> bool {
+        if rows.is_empty() {
+            let garbage = std::mem::take(&mut self.incoming[player]);
+            self.players[player].add_garbage(garbage)
+        } else {
+            let attack = self.attack.attack(clear);
+            let cancelled = attack.min(self.incoming[player]);
+            self.incoming[player] -= cancelled;
+            self.incoming[1 - player] += attack - cancelled;
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{
+        scoring::TSpin, DasSettings, Game, GameMode, PieceGenerator, RandomizerKind, Ruleset,
+    };
+
+    fn new_versus() -> VersusGame {
+        let player = || {
+            let game = Game::new(
+                GameMode::Marathon,
+                Ruleset::Modern,
+                0,
+                0,
+                PieceGenerator::new(3, RandomizerKind::SevenBag, 1),
+            );
+            Simulation::new(game, DasSettings::default())
+        };
+        VersusGame::new([player(), player()], AttackTable::default())
+    }
+
+    fn clear(lines: usize) -> Clear {
+        Clear {
+            lines,
+            t_spin: TSpin::None,
+            combo: 0,
+            back_to_back: false,
+            perfect_clear: false,
+            points: 0,
+        }
+    }
+
+    #[test]
+    fn clears_cancel_incoming_garbage_before_sending() {
+        let mut versus = new_versus();
+
+        // A tetris sends 4
+        assert!(!versus.locked(1, &[16, 17, 18, 19], &clear(4)));
+        assert_eq!(versus.incoming(0), 4);
+
+        // A triple cancels 2 of them and sends nothing
+        assert!(!versus.locked(0, &[17, 18, 19], &clear(3)));
+        assert_eq!(versus.incoming(0), 2);
+        assert_eq!(versus.incoming(1), 0);
+
+        // A single sends nothing, so cancels nothing
+        assert!(!versus.locked(0, &[19], &clear(1)));
+        assert_eq!(versus.incoming(0), 2);
+
+        // The rest lands when the next piece locks without clearing
+        assert_eq!(versus.player(0).game().garbage_rows(), 0);
+        assert!(!versus.locked(0, &[], &clear(0)));
+        assert_eq!(versus.incoming(0), 0);
+        assert_eq!(versus.player(0).game().garbage_rows(), 2);
+        assert_eq!(versus.player(1).game().garbage_rows(), 0);
+    }
+
+    #[test]
+    fn clears_beyond_the_incoming_garbage_send_the_rest() {
+        let mut versus = new_versus();
+        versus.incoming[0] = 1;
+
+        assert!(!versus.locked(0, &[16, 17, 18, 19], &clear(4)));
+        assert_eq!(versus.incoming(0), 0);
+        assert_eq!(versus.incoming(1), 3);
+    }
+
+    #[test]
+    fn landing_garbage_can_top_out() {
+        let mut versus = new_versus();
+        versus.incoming[1] = 40;
+
+        assert!(versus.locked(1, &[], &clear(0)));
+        assert_eq!(versus.incoming(1), 0);
+    }
+}
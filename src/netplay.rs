@@ -0,0 +1,722 @@
+//! Versus over TCP, with both players running both games in lockstep
+//!
+//! The host picks the settings and waits for the other player to connect.
+//! Each side opens with the magic bytes `QNET` and a protocol version, then
+//! the host sends the game settings and its DAS settings and the other player
+//! answers with theirs. The host is player 0.
+//!
+//! After that each side sends one message per frame of play with the buttons
+//! its player held, applied a few frames later so they have time to arrive.
+//! Messages during play carry the round they belong to, so anything still in
+//! flight when a round ends isn't mistaken for the next one.
+//! A frame is only played once both players' input for it is in, so both
+//! copies of each game see the same input on the same frame. Every second
+//! each side also sends a hash of its boards and scores, and the game stops
+//! if they differ. All integers are little-endian.
+
+use crate::model::{
+    AttackTable, DasSettings, Event, Game, GameMode, InputFrame, MersenneTwister, Outcome,
+    PieceGenerator, RandomizerKind, Ruleset, Simulation, VersusGame, MAX_NEXT_QUEUE_LENGTH,
+};
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    io::{ErrorKind, Read, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+};
+
+/// What both players agree to play
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Settings {
+    pub ruleset: Ruleset,
+    pub starting_level: usize,
+    pub next_queue_length: usize,
+    pub randomizer: RandomizerKind,
+    pub seed: u32,
+    pub attack: AttackTable,
+}
+
+/// A connection to the other player, once the handshake is done
+pub struct Connection {
+    stream: TcpStream,
+    player: usize,
+    settings: Settings,
+    das: [DasSettings; 2],
+}
+
+/// A versus match played with the other end of a [`Connection`]
+pub struct Lockstep {
+    stream: TcpStream,
+    player: usize,
+    settings: Settings,
+    das: [DasSettings; 2],
+
+    versus: VersusGame,
+    seed: u32,
+    // Counts up from 0, wrapping, so messages can be matched to their round
+    round: u8,
+    frame: usize,
+
+    // Inputs for the frames not played yet, oldest first. The other player's
+    // are tagged with their round.
+    local: VecDeque<InputFrame>,
+    remote: VecDeque<(u8, InputFrame)>,
+    // Hashes not yet compared with the other side's, as round, frame and hash
+    local_hashes: VecDeque<(u8, u32, u32)>,
+    remote_hashes: VecDeque<(u8, u32, u32)>,
+    // Bytes read that don't make up a whole message yet
+    received: Vec<u8>,
+    // Bytes the socket wasn't ready to take yet, sent before anything else
+    unsent: Vec<u8>,
+
+    local_rematch: bool,
+    remote_rematch: bool,
+}
+
+#[derive(Debug)]
+pub enum NetError {
+    Io(std::io::Error),
+    NotAPeer,
+    UnsupportedVersion(u8),
+    Invalid,
+    Disconnected,
+    Desync { frame: u32 },
+}
+
+const MAGIC: &[u8; 4] = b"QNET";
+const VERSION: u8 = 1;
+
+/// Frames between a button being read and its frame being played
+pub const INPUT_DELAY: usize = 3;
+/// Frames between hashes
+pub const HASH_INTERVAL: usize = 60;
+
+const INPUT: u8 = 0;
+const HASH: u8 = 1;
+const REMATCH: u8 = 2;
+const QUIT: u8 = 3;
+
+fn read_u8<R: Read>(reader: &mut R) -> Result<u8, NetError> {
+    let mut buffer = [0; 1];
+    reader.read_exact(&mut buffer)?;
+    Ok(buffer[0])
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> Result<u32, NetError> {
+    let mut buffer = [0; 4];
+    reader.read_exact(&mut buffer)?;
+    Ok(u32::from_le_bytes(buffer))
+}
+
+fn write_hello<W: Write>(writer: &mut W) -> Result<(), NetError> {
+    writer.write_all(MAGIC)?;
+    writer.write_all(&[VERSION])?;
+    Ok(())
+}
+
+fn read_hello<R: Read>(reader: &mut R) -> Result<(), NetError> {
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(NetError::NotAPeer);
+    }
+
+    match read_u8(reader)? {
+        VERSION => Ok(()),
+        version => Err(NetError::UnsupportedVersion(version)),
+    }
+}
+
+fn write_das<W: Write>(writer: &mut W, das: DasSettings) -> Result<(), NetError> {
+    writer.write_all(&[das.initial_delay, das.repeat_delay])?;
+    Ok(())
+}
+
+fn read_das<R: Read>(reader: &mut R) -> Result<DasSettings, NetError> {
+    let das = DasSettings {
+        initial_delay: read_u8(reader)?,
+        repeat_delay: read_u8(reader)?,
+    };
+
+    if das.is_valid() {
+        Ok(das)
+    } else {
+        Err(NetError::Invalid)
+    }
+}
+
+impl Settings {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<(), NetError> {
+        writer.write_all(&[
+            match self.ruleset {
+                Ruleset::Classic => 0,
+                Ruleset::Modern => 1,
+            },
+            self.next_queue_length as u8,
+            self.randomizer as u8,
+        ])?;
+        writer.write_all(&(self.starting_level as u32).to_le_bytes())?;
+        writer.write_all(&self.seed.to_le_bytes())?;
+
+        let attack = &self.attack;
+        for rows in attack.lines.iter().chain(&attack.t_spin) {
+            writer.write_all(&(*rows as u32).to_le_bytes())?;
+        }
+        writer.write_all(&(attack.back_to_back as u32).to_le_bytes())?;
+        writer.write_all(&(attack.perfect_clear as u32).to_le_bytes())?;
+        Ok(())
+    }
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self, NetError> {
+        let ruleset = match read_u8(reader)? {
+            0 => Ruleset::Classic,
+            1 => Ruleset::Modern,
+            _ => return Err(NetError::Invalid),
+        };
+        let next_queue_length = read_u8(reader)? as usize;
+        if !(1..=MAX_NEXT_QUEUE_LENGTH).contains(&next_queue_length) {
+            return Err(NetError::Invalid);
+        }
+        let randomizer = *RandomizerKind::ALL
+            .get(read_u8(reader)? as usize)
+            .ok_or(NetError::Invalid)?;
+        let starting_level = read_u32(reader)? as usize;
+        let seed = read_u32(reader)?;
+
+        let mut attack = AttackTable::default();
+        for rows in attack.lines.iter_mut().chain(&mut attack.t_spin) {
+            *rows = read_u32(reader)? as usize;
+        }
+        attack.back_to_back = read_u32(reader)? as usize;
+        attack.perfect_clear = read_u32(reader)? as usize;
+
+        Ok(Settings {
+            ruleset,
+            starting_level,
+            next_queue_length,
+            randomizer,
+            seed,
+            attack,
+        })
+    }
+}
+
+impl Connection {
+    /// Waits on `port` for the other player, offering them `settings`
+    pub fn host(port: u16, settings: Settings, das: DasSettings) -> Result<Self, NetError> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        Connection::accept(&listener, settings, das)
+    }
+
+    fn accept(
+        listener: &TcpListener,
+        settings: Settings,
+        das: DasSettings,
+    ) -> Result<Self, NetError> {
+        let (mut stream, _) = listener.accept()?;
+        stream.set_nodelay(true)?;
+
+        write_hello(&mut stream)?;
+        settings.write(&mut stream)?;
+        write_das(&mut stream, das)?;
+
+        read_hello(&mut stream)?;
+        let remote_das = read_das(&mut stream)?;
+
+        Ok(Connection {
+            stream,
+            player: 0,
+            settings,
+            das: [das, remote_das],
+        })
+    }
+
+    /// Joins the game hosted at `address`, taking the host's settings
+    pub fn connect<A: ToSocketAddrs>(address: A, das: DasSettings) -> Result<Self, NetError> {
+        let mut stream = TcpStream::connect(address)?;
+        stream.set_nodelay(true)?;
+
+        read_hello(&mut stream)?;
+        let settings = Settings::read(&mut stream)?;
+        let remote_das = read_das(&mut stream)?;
+
+        write_hello(&mut stream)?;
+        write_das(&mut stream, das)?;
+
+        Ok(Connection {
+            stream,
+            player: 1,
+            settings,
+            das: [remote_das, das],
+        })
+    }
+
+    /// This side's player, 0 for the host
+    pub fn player(&self) -> usize {
+        self.player
+    }
+
+    pub fn settings(&self) -> Settings {
+        self.settings
+    }
+}
+
+// Both boards and scores, so a game that went differently shows up even if
+// the boards happen to match
+fn state_hash(versus: &VersusGame) -> u32 {
+    (0..2).fold(0, |hash, player| {
+        let game = versus.player(player).game();
+        let hash = hash.rotate_left(5) ^ game.board().checksum();
+        hash.rotate_left(5) ^ game.score() as u32
+    })
+}
+
+impl Lockstep {
+    pub fn new(connection: Connection) -> Result<Self, NetError> {
+        let Connection {
+            stream,
+            player,
+            settings,
+            das,
+        } = connection;
+        // Nothing would be drawn while waiting for the other player
+        stream.set_nonblocking(true)?;
+
+        let mut lockstep = Lockstep {
+            stream,
+            player,
+            settings,
+            das,
+            versus: new_versus(&settings, das, settings.seed),
+            seed: settings.seed,
+            round: 0,
+            frame: 0,
+            local: VecDeque::new(),
+            remote: VecDeque::new(),
+            local_hashes: VecDeque::new(),
+            remote_hashes: VecDeque::new(),
+            received: Vec::new(),
+            unsent: Vec::new(),
+            local_rematch: false,
+            remote_rematch: false,
+        };
+        lockstep.start_round();
+        Ok(lockstep)
+    }
+
+    /// This side's player, 0 for the host
+    pub fn player(&self) -> usize {
+        self.player
+    }
+
+    pub fn settings(&self) -> Settings {
+        self.settings
+    }
+
+    pub fn versus(&self) -> &VersusGame {
+        &self.versus
+    }
+
+    /// Takes a player's events since the last call, oldest first
+    pub fn take_events(&mut self, player: usize) -> Vec<Event> {
+        self.versus.take_events(player)
+    }
+
+    /// Frames played this round
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    /// Sends this player's buttons and plays the next frame if the other
+    /// player's input for it has arrived. Returns the outcome once the round
+    /// is over; after that only [`Lockstep::request_rematch`] and
+    /// [`Lockstep::poll_rematch`] should be called.
+    pub fn update(&mut self, input: InputFrame) -> Result<Option<Outcome>, NetError> {
+        // While waiting on the other player, buttons are dropped rather than
+        // piling up and adding to the delay
+        if self.local.len() <= INPUT_DELAY {
+            self.send(&[INPUT, self.round, input.bits()])?;
+            self.local.push_back(input);
+        } else {
+            self.flush()?;
+        }
+
+        self.receive()?;
+
+        let remote = match self.remote.front() {
+            Some((round, input)) if *round == self.round => *input,
+            _ => return Ok(None),
+        };
+        let local = match self.local.pop_front() {
+            Some(input) => input,
+            None => return Ok(None),
+        };
+        self.remote.pop_front();
+
+        let mut inputs = [InputFrame::default(); 2];
+        inputs[self.player] = local;
+        inputs[1 - self.player] = remote;
+        let outcome = self.versus.step(inputs);
+        self.frame += 1;
+
+        if self.frame.is_multiple_of(HASH_INTERVAL) {
+            let frame = self.frame as u32;
+            let hash = state_hash(&self.versus);
+            let mut message = vec![HASH, self.round];
+            message.extend_from_slice(&frame.to_le_bytes());
+            message.extend_from_slice(&hash.to_le_bytes());
+            self.send(&message)?;
+            self.local_hashes.push_back((self.round, frame, hash));
+        }
+        self.compare_hashes()?;
+
+        Ok(outcome)
+    }
+
+    /// Asks for another round, once this one is over
+    pub fn request_rematch(&mut self) -> Result<(), NetError> {
+        if !self.local_rematch {
+            self.local_rematch = true;
+            self.send(&[REMATCH])?;
+        }
+
+        Ok(())
+    }
+
+    /// Starts the next round once both players have asked for it, returning
+    /// true when it has started
+    pub fn poll_rematch(&mut self) -> Result<bool, NetError> {
+        self.flush()?;
+        self.receive()?;
+        if !(self.local_rematch && self.remote_rematch) {
+            return Ok(false);
+        }
+
+        // Each round is dealt from the seed after the last, so the players
+        // don't get the same pieces every round
+        self.seed = MersenneTwister::new(self.seed).next_number();
+        self.versus = new_versus(&self.settings, self.das, self.seed);
+        self.round = self.round.wrapping_add(1);
+        self.start_round();
+        Ok(true)
+    }
+
+    /// Tells the other player this one has left
+    pub fn quit(&mut self) {
+        // They find out anyway once the connection closes
+        let _ = self.send(&[QUIT]);
+    }
+
+    fn start_round(&mut self) {
+        self.frame = 0;
+        self.local_rematch = false;
+        self.remote_rematch = false;
+
+        // Whatever is left from the last round was never needed
+        self.local.clear();
+        self.local_hashes.clear();
+        while let Some((round, _)) = self.remote.front() {
+            if *round == self.round {
+                break;
+            }
+            self.remote.pop_front();
+        }
+        self.remote_hashes
+            .retain(|(round, ..)| *round == self.round);
+
+        // The first few frames have no input, while the first real input is
+        // on its way. The other player's input for the new round may already
+        // be waiting, so theirs go in front.
+        for _ in 0..INPUT_DELAY {
+            self.local.push_back(InputFrame::default());
+            self.remote.push_front((self.round, InputFrame::default()));
+        }
+    }
+
+    fn send(&mut self, message: &[u8]) -> Result<(), NetError> {
+        self.unsent.extend_from_slice(message);
+        self.flush()
+    }
+
+    // Writes as much of the unsent bytes as the socket takes. The stream
+    // doesn't block, so a message can go out in pieces over several frames.
+    fn flush(&mut self) -> Result<(), NetError> {
+        while !self.unsent.is_empty() {
+            match self.stream.write(&self.unsent) {
+                Ok(0) => return Err(NetError::Disconnected),
+                Ok(length) => {
+                    self.unsent.drain(..length);
+                }
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == ErrorKind::Interrupted => {}
+                Err(error) => return Err(error.into()),
+            }
+        }
+
+        Ok(())
+    }
+
+    // Reads every message that has arrived
+    fn receive(&mut self) -> Result<(), NetError> {
+        let mut buffer = [0; 256];
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => return Err(NetError::Disconnected),
+                Ok(length) => self.received.extend_from_slice(&buffer[..length]),
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == ErrorKind::Interrupted => {}
+                Err(error) => return Err(error.into()),
+            }
+        }
+
+        let mut start = 0;
+        while let Some(tag) = self.received.get(start) {
+            let length = match *tag {
+                INPUT => 3,
+                HASH => 10,
+                REMATCH | QUIT => 1,
+                _ => return Err(NetError::Invalid),
+            };
+            let message = match self.received.get(start..start + length) {
+                Some(message) => message,
+                None => break,
+            };
+
+            match message[0] {
+                INPUT => {
+                    let input = InputFrame::from_bits(message[2]).ok_or(NetError::Invalid)?;
+                    self.remote.push_back((message[1], input));
+                }
+                HASH => {
+                    let frame = u32::from_le_bytes(message[2..6].try_into().unwrap());
+                    let hash = u32::from_le_bytes(message[6..10].try_into().unwrap());
+                    self.remote_hashes.push_back((message[1], frame, hash));
+                }
+                REMATCH => self.remote_rematch = true,
+                _ => return Err(NetError::Disconnected),
+            }
+
+            start += length;
+        }
+        self.received.drain(..start);
+
+        self.compare_hashes()
+    }
+
+    fn compare_hashes(&mut self) -> Result<(), NetError> {
+        // Hashes from different rounds wait for the next round to start,
+        // which throws the old ones away
+        while let (Some(local), Some(remote)) =
+            (self.local_hashes.front(), self.remote_hashes.front())
+        {
+            if local.0 != remote.0 {
+                break;
+            }
+
+            if local != remote {
+                return Err(NetError::Desync { frame: local.1 });
+            }
+
+            self.local_hashes.pop_front();
+            self.remote_hashes.pop_front();
+        }
+
+        Ok(())
+    }
+}
+
+fn new_versus(settings: &Settings, das: [DasSettings; 2], seed: u32) -> VersusGame {
+    let new_player = |das| {
+        let piece_generator =
+            PieceGenerator::new(seed, settings.randomizer, settings.next_queue_length);
+        let game = Game::new(
+            GameMode::Marathon,
+            settings.ruleset,
+            settings.starting_level,
+            // Versus is never Dig
+            0,
+            piece_generator,
+        );
+        Simulation::new(game, das)
+    };
+
+    VersusGame::new([new_player(das[0]), new_player(das[1])], settings.attack)
+}
+
+impl Display for NetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NetError::Io(error) => error.fmt(f),
+            NetError::NotAPeer => write!(f, "the other end isn't running this game"),
+            NetError::UnsupportedVersion(version) => {
+                write!(f, "the other player has protocol version {}", version)
+            }
+            NetError::Invalid => write!(f, "the other player sent something invalid"),
+            NetError::Disconnected => write!(f, "the other player left"),
+            NetError::Desync { frame } => {
+                write!(f, "the games stopped matching by frame {}", frame)
+            }
+        }
+    }
+}
+
+impl std::error::Error for NetError {}
+
+impl From<std::io::Error> for NetError {
+    fn from(error: std::io::Error) -> Self {
+        NetError::Io(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Action;
+    use std::{
+        thread,
+        time::{Duration, Instant},
+    };
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    fn settings() -> Settings {
+        Settings {
+            ruleset: Ruleset::Modern,
+            starting_level: 0,
+            next_queue_length: 3,
+            randomizer: RandomizerKind::SevenBag,
+            seed: 99,
+            attack: AttackTable::default(),
+        }
+    }
+
+    /// Hosts on a free port on this machine and joins from another thread,
+    /// returning the host's side then the other player's
+    fn loopback() -> [Lockstep; 2] {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let host_das = DasSettings {
+            initial_delay: 10,
+            repeat_delay: 2,
+        };
+
+        let host = thread::spawn(move || Connection::accept(&listener, settings(), host_das));
+        let client = Connection::connect(address, DasSettings::default()).unwrap();
+        let host = host.join().unwrap().unwrap();
+
+        assert_eq!((host.player(), client.player()), (0, 1));
+        assert_eq!(client.settings(), settings());
+        assert_eq!(host.das, [host_das, DasSettings::default()]);
+        assert_eq!(client.das, host.das);
+
+        [Lockstep::new(host).unwrap(), Lockstep::new(client).unwrap()]
+    }
+
+    /// Some shifting, rotating and a hard drop every half second, different
+    /// for each player
+    fn scripted_input(player: usize, call: usize) -> InputFrame {
+        let mut input = InputFrame::default();
+        let action = match (call + player * 7) % 30 {
+            2 | 6 => Action::MoveLeft,
+            4 if player == 0 => Action::RotateClockwise,
+            4 => Action::RotateCounterclockwise,
+            10 | 12 | 14 => Action::MoveRight,
+            20 => Action::HardDrop,
+            _ => return input,
+        };
+        input.hold(action);
+        input
+    }
+
+    /// Updates both sides with their player's script until both have played
+    /// `frames` frames
+    fn play(sides: &mut [Lockstep; 2], frames: usize) -> Result<(), NetError> {
+        let mut calls = [0; 2];
+        let start = Instant::now();
+        while sides.iter().any(|side| side.frame() < frames) {
+            for (player, side) in sides.iter_mut().enumerate() {
+                if side.frame() < frames {
+                    assert_eq!(side.update(scripted_input(player, calls[player]))?, None);
+                    calls[player] += 1;
+                }
+            }
+            assert!(start.elapsed() < TIMEOUT, "stuck at {}", sides[0].frame());
+        }
+
+        Ok(())
+    }
+
+    /// Reads until each side has compared every hash it sent
+    fn compare_all_hashes(sides: &mut [Lockstep; 2]) -> Result<(), NetError> {
+        let start = Instant::now();
+        for side in sides.iter_mut() {
+            while !side.local_hashes.is_empty() {
+                side.receive()?;
+                assert!(start.elapsed() < TIMEOUT);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn both_sides_play_the_same_game() {
+        let mut sides = loopback();
+        let frames = 4 * HASH_INTERVAL;
+        play(&mut sides, frames).unwrap();
+        compare_all_hashes(&mut sides).unwrap();
+
+        assert_eq!(sides[0].frame(), frames);
+        assert_eq!(sides[1].frame(), frames);
+        assert_eq!(state_hash(sides[0].versus()), state_hash(sides[1].versus()));
+        for player in 0..2 {
+            let games = sides
+                .each_ref()
+                .map(|side| side.versus().player(player).game());
+            assert!(games[0].score() > 0);
+            assert_eq!(games[0].score(), games[1].score());
+            assert_eq!(games[0].board().checksum(), games[1].board().checksum());
+        }
+    }
+
+    #[test]
+    fn a_changed_input_is_a_desync() {
+        let mut sides = loopback();
+        play(&mut sides, 30).unwrap();
+
+        // The other player is sent blank frames, but this side hard drops on
+        // every other one of them, so some land while a piece is in play
+        let start = Instant::now();
+        for call in 0..20 {
+            sides[0].update(InputFrame::default()).unwrap();
+            let sent = sides[1].local.len() <= INPUT_DELAY;
+            sides[1].update(InputFrame::default()).unwrap();
+            if call % 2 == 0 && sent {
+                sides[1].local.back_mut().unwrap().hold(Action::HardDrop);
+            }
+            assert!(start.elapsed() < TIMEOUT);
+        }
+
+        let result = play(&mut sides, HASH_INTERVAL).and_then(|()| compare_all_hashes(&mut sides));
+        match result {
+            Err(NetError::Desync { frame }) => assert_eq!(frame, HASH_INTERVAL as u32),
+            other => panic!("expected a desync, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn quitting_is_a_disconnect() {
+        let [mut host, mut client] = loopback();
+        client.quit();
+        drop(client);
+
+        let start = Instant::now();
+        loop {
+            match host.update(InputFrame::default()) {
+                Ok(_) => assert!(start.elapsed() < TIMEOUT),
+                Err(NetError::Disconnected) => break,
+                Err(error) => panic!("expected a disconnect, got {}", error),
+            }
+        }
+    }
+}
@@ -0,0 +1,361 @@
+//! Recordings of the input seen on every frame of a game
+//!
+//! A replay file starts with the magic bytes `QRPL` and a format version,
+//! followed by the game and DAS settings and one byte of held buttons per
+//! frame. All integers are little-endian.
+
+use crate::model::{
+    DasSettings, Game, GameMode, InputFrame, PieceGenerator, RandomizerKind, Ruleset, Simulation,
+    MAX_DIG_ROWS, MAX_NEXT_QUEUE_LENGTH,
+};
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+pub struct Replay {
+    mode: GameMode,
+    ruleset: Ruleset,
+    starting_level: usize,
+    dig_rows: usize,
+    next_queue_length: usize,
+    randomizer: RandomizerKind,
+    seed: u32,
+    das: DasSettings,
+    frames: Vec<InputFrame>,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(std::io::Error),
+    NotAReplay,
+    UnsupportedVersion(u8),
+    Invalid,
+}
+
+const MAGIC: &[u8; 4] = b"QRPL";
+const VERSION: u8 = 7;
+
+fn read_u8<R: Read>(reader: &mut R) -> Result<u8, ReplayError> {
+    let mut buffer = [0; 1];
+    reader.read_exact(&mut buffer)?;
+    Ok(buffer[0])
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> Result<u32, ReplayError> {
+    let mut buffer = [0; 4];
+    reader.read_exact(&mut buffer)?;
+    Ok(u32::from_le_bytes(buffer))
+}
+
+impl Replay {
+    // One argument for each setting a game is recreated from
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        mode: GameMode,
+        ruleset: Ruleset,
+        starting_level: usize,
+        dig_rows: usize,
+        next_queue_length: usize,
+        randomizer: RandomizerKind,
+        seed: u32,
+        das: DasSettings,
+    ) -> Self {
+        Replay {
+            mode,
+            ruleset,
+            starting_level,
+            dig_rows,
+            next_queue_length,
+            randomizer,
+            seed,
+            das,
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ReplayError> {
+        Replay::read(&mut BufReader::new(File::open(path)?))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ReplayError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self, ReplayError> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(ReplayError::NotAReplay);
+        }
+
+        let version = read_u8(reader)?;
+        if version != VERSION {
+            return Err(ReplayError::UnsupportedVersion(version));
+        }
+
+        let mode = *GameMode::ALL
+            .get(read_u8(reader)? as usize)
+            .ok_or(ReplayError::Invalid)?;
+        let ruleset = match read_u8(reader)? {
+            0 => Ruleset::Classic,
+            1 => Ruleset::Modern,
+            _ => return Err(ReplayError::Invalid),
+        };
+        let starting_level = read_u32(reader)? as usize;
+        let dig_rows = read_u8(reader)? as usize;
+        if !(1..=MAX_DIG_ROWS).contains(&dig_rows) {
+            return Err(ReplayError::Invalid);
+        }
+        let next_queue_length = read_u8(reader)? as usize;
+        if !(1..=MAX_NEXT_QUEUE_LENGTH).contains(&next_queue_length) {
+            return Err(ReplayError::Invalid);
+        }
+        let randomizer = *RandomizerKind::ALL
+            .get(read_u8(reader)? as usize)
+            .ok_or(ReplayError::Invalid)?;
+        let seed = read_u32(reader)?;
+        let das = DasSettings {
+            initial_delay: read_u8(reader)?,
+            repeat_delay: read_u8(reader)?,
+        };
+        if !das.is_valid() {
+            return Err(ReplayError::Invalid);
+        }
+
+        let frame_count = read_u32(reader)? as usize;
+        let mut bits = Vec::new();
+        reader.read_to_end(&mut bits)?;
+        if bits.len() != frame_count {
+            return Err(ReplayError::Invalid);
+        }
+
+        let frames = bits
+            .into_iter()
+            .map(InputFrame::from_bits)
+            .collect::<Option<Vec<_>>>()
+            .ok_or(ReplayError::Invalid)?;
+
+        Ok(Replay {
+            mode,
+            ruleset,
+            starting_level,
+            dig_rows,
+            next_queue_length,
+            randomizer,
+            seed,
+            das,
+            frames,
+        })
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), ReplayError> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[
+            VERSION,
+            self.mode as u8,
+            match self.ruleset {
+                Ruleset::Classic => 0,
+                Ruleset::Modern => 1,
+            },
+        ])?;
+        writer.write_all(&(self.starting_level as u32).to_le_bytes())?;
+        writer.write_all(&[
+            self.dig_rows as u8,
+            self.next_queue_length as u8,
+            self.randomizer as u8,
+        ])?;
+        writer.write_all(&self.seed.to_le_bytes())?;
+        writer.write_all(&[self.das.initial_delay, self.das.repeat_delay])?;
+        writer.write_all(&(self.frames.len() as u32).to_le_bytes())?;
+
+        let bits: Vec<u8> = self.frames.iter().map(|frame| frame.bits()).collect();
+        writer.write_all(&bits)?;
+        Ok(())
+    }
+
+    pub fn mode(&self) -> GameMode {
+        self.mode
+    }
+
+    pub fn ruleset(&self) -> Ruleset {
+        self.ruleset
+    }
+
+    pub fn starting_level(&self) -> usize {
+        self.starting_level
+    }
+
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+
+    pub fn das(&self) -> DasSettings {
+        self.das
+    }
+
+    pub fn frames(&self) -> &[InputFrame] {
+        &self.frames
+    }
+
+    pub fn push(&mut self, frame: InputFrame) {
+        self.frames.push(frame);
+    }
+
+    /// Creates the game this replay was recorded from
+    pub fn new_game(&self) -> Game {
+        Game::new(
+            self.mode,
+            self.ruleset,
+            self.starting_level,
+            self.dig_rows,
+            PieceGenerator::new(self.seed, self.randomizer, self.next_queue_length),
+        )
+    }
+
+    /// Plays the whole replay without rendering, returning the final state
+    pub fn run(&self) -> Simulation {
+        let mut simulation = Simulation::new(self.new_game(), self.das);
+        for frame in &self.frames {
+            let game_over = simulation.step(*frame);
+            // Nothing is listening, so the events would only pile up
+            simulation.take_events();
+            if game_over {
+                break;
+            }
+        }
+
+        simulation
+    }
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayError::Io(error) => error.fmt(f),
+            ReplayError::NotAReplay => write!(f, "not a replay file"),
+            ReplayError::UnsupportedVersion(version) => {
+                write!(f, "unsupported replay version {}", version)
+            }
+            ReplayError::Invalid => write!(f, "the replay is corrupt"),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<std::io::Error> for ReplayError {
+    fn from(error: std::io::Error) -> Self {
+        ReplayError::Io(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Action, DEFAULT_DIG_ROWS};
+
+    /// Input that spreads the pieces across the board: each piece gets some
+    /// rotations and shifts, one press every other frame, then a hard drop
+    fn scripted_input(pieces: usize) -> Vec<InputFrame> {
+        let mut frames = Vec::new();
+        let mut press = |action: Option<Action>| {
+            let mut frame = InputFrame::default();
+            if let Some(action) = action {
+                frame.hold(action);
+            }
+            frames.push(frame);
+            frames.push(InputFrame::default());
+        };
+
+        for piece in 0..pieces {
+            for _ in 0..piece % 4 {
+                press(Some(Action::RotateClockwise));
+            }
+            let (direction, shifts) = match piece % 9 {
+                shifts @ 0..=4 => (Action::MoveLeft, shifts),
+                shifts => (Action::MoveRight, shifts - 4),
+            };
+            for _ in 0..shifts {
+                press(Some(direction));
+            }
+            press(Some(Action::HardDrop));
+            // Wait out the entry delay and any line clear
+            for _ in 0..30 {
+                press(None);
+            }
+        }
+
+        frames
+    }
+
+    #[test]
+    fn written_replay_plays_back_the_same_game() {
+        let mut replay = Replay::new(
+            GameMode::Marathon,
+            Ruleset::Modern,
+            5,
+            DEFAULT_DIG_ROWS,
+            3,
+            RandomizerKind::SevenBag,
+            // A seed whose pieces happen to clear lines with this input
+            934,
+            DasSettings::default(),
+        );
+        let mut simulation = Simulation::new(replay.new_game(), DasSettings::default());
+        for frame in scripted_input(200) {
+            replay.push(frame);
+            let game_over = simulation.step(frame);
+            simulation.take_events();
+            if game_over {
+                break;
+            }
+        }
+
+        let mut file = Vec::new();
+        replay.write(&mut file).unwrap();
+        let read = Replay::read(&mut file.as_slice()).unwrap();
+        assert_eq!(read.frames(), replay.frames());
+
+        let played = read.run();
+        assert_eq!(played.frame(), simulation.frame());
+
+        let (recorded, played) = (simulation.game(), played.game());
+        assert!(recorded.total_lines() > 0);
+        assert_eq!(played.score(), recorded.score());
+        assert_eq!(played.total_lines(), recorded.total_lines());
+        assert_eq!(played.board().checksum(), recorded.board().checksum());
+    }
+
+    #[test]
+    fn marathon_and_b_type_keep_their_mode_bytes() {
+        // Marathon and B-type are written as the A and B game types were
+        for (mode, byte) in [(GameMode::Marathon, 0), (GameMode::BType, 1)] {
+            let replay = Replay::new(
+                mode,
+                Ruleset::Classic,
+                9,
+                DEFAULT_DIG_ROWS,
+                1,
+                RandomizerKind::Nes,
+                0x01020304,
+                DasSettings::default(),
+            );
+            let mut file = Vec::new();
+            replay.write(&mut file).unwrap();
+
+            let mut expected = b"QRPL".to_vec();
+            expected.extend([VERSION, byte, 0, 9, 0, 0, 0]);
+            expected.extend([DEFAULT_DIG_ROWS as u8, 1, RandomizerKind::Nes as u8]);
+            expected.extend([4, 3, 2, 1, 16, 6, 0, 0, 0, 0]);
+            assert_eq!(file, expected);
+
+            assert_eq!(Replay::read(&mut file.as_slice()).unwrap().mode(), mode);
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use super::{Text, Textures};
+use crate::{
+    config::{key_name, KeyBindings},
+    model::{Action, BOARD_WIDTH},
+};
+use colosseum::{Input, Vector2, Window};
+
+pub struct ControlsUI {
+    labels: Box<[Text]>,
+    rows: Box<[Text]>,
+
+    selected: usize,
+    frame_counter: usize,
+}
+
+const CENTER_X: f32 = BOARD_WIDTH as f32 / 2.0 - 0.5;
+const FIRST_ROW_Y: f32 = 16.0;
+
+const BLINK_FRAMES: usize = 16;
+
+fn action_label(action: Action) -> &'static str {
+    match action {
+        Action::MoveLeft => "MOVE LEFT",
+        Action::MoveRight => "MOVE RIGHT",
+        Action::SoftDrop => "SOFT DROP",
+        Action::RotateCounterclockwise => "ROTATE CCW",
+        Action::RotateClockwise => "ROTATE CW",
+        Action::HardDrop => "HARD DROP",
+        Action::Hold => "HOLD",
+        Action::Pause => "PAUSE",
+    }
+}
+
+fn row_text(action: Action, bindings: &KeyBindings) -> String {
+    let keys: Vec<String> = bindings
+        .keys(action)
+        .iter()
+        .map(|key| key_name(*key).to_uppercase())
+        .collect();
+
+    format!("{:<11} {:<13}", action_label(action), keys.join(" "))
+}
+
+fn row_position(index: usize) -> Vector2 {
+    Vector2::new(CENTER_X, FIRST_ROW_Y - index as f32)
+}
+
+impl ControlsUI {
+    pub fn new(bindings: &KeyBindings, textures: &Textures) -> Self {
+        let mut labels = Vec::with_capacity(3);
+        for (label, y) in [("CONTROLS", 18.0), ("PRESS A KEY", 5.0), ("ENTER SKIPS", 4.0)] {
+            let mut text = Text::new(label, textures);
+            text.set_position(Vector2::new(CENTER_X, y));
+            labels.push(text);
+        }
+
+        let rows = Action::ALL
+            .iter()
+            .enumerate()
+            .map(|(i, action)| {
+                let mut text = Text::new(&row_text(*action, bindings), textures);
+                text.set_position(row_position(i));
+                text
+            })
+            .collect();
+
+        ControlsUI {
+            labels: labels.into_boxed_slice(),
+            rows,
+            selected: 0,
+            frame_counter: 0,
+        }
+    }
+
+    pub fn set_row(&mut self, index: usize, bindings: &KeyBindings, textures: &Textures) {
+        let mut text = Text::new(&row_text(Action::ALL[index], bindings), textures);
+        text.set_position(row_position(index));
+        self.rows[index] = text;
+    }
+
+    pub fn select(&mut self, index: usize) {
+        self.selected = index;
+        self.frame_counter = 0;
+    }
+
+    pub fn update(&mut self) {
+        self.frame_counter += 1;
+    }
+
+    pub fn render<I: Input>(&mut self, window: &mut Window<I>) {
+        for label in self.labels.iter_mut() {
+            label.render(window);
+        }
+
+        // The row being rebound blinks
+        let blink = (self.frame_counter / BLINK_FRAMES) % 2 == 1;
+        for (i, row) in self.rows.iter_mut().enumerate() {
+            if !(blink && i == self.selected) {
+                row.render(window);
+            }
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use super::{Number, ScoreNumber, Text, Textures};
+use crate::{
+    config::{LevelDisplay, ScoreDisplay},
+    model::{Game, GameMode, BOARD_WIDTH},
+};
+use colosseum::{Input, Vector2, Window};
+
+pub struct GameOverUI {
+    labels: Box<[Text]>,
+
+    score: ScoreNumber,
+    lines: Number<3>,
+    // Timed modes show their time in place of the level
+    level: Option<Number<2>>,
+    time: Option<Text>,
+    seed: Text,
+}
+
+const CENTER_X: f32 = BOARD_WIDTH as f32 / 2.0 - 0.5;
+
+impl GameOverUI {
+    pub fn new<I: Input>(
+        game: &Game,
+        level_display: LevelDisplay,
+        score_display: ScoreDisplay,
+        textures: &Textures,
+        window: &mut Window<I>,
+    ) -> Self {
+        let (title_top, title_bottom) = match (game.is_complete(), game.mode()) {
+            (true, GameMode::Ultra) => ("TIME", "UP"),
+            (true, _) => ("WELL", "DONE"),
+            (false, _) => ("GAME", "OVER"),
+        };
+        let timer = game.timer().map(|_| game.frames());
+
+        let mut labels = Vec::with_capacity(8);
+        for (label, y) in [
+            (title_top, 19.0),
+            (title_bottom, 18.0),
+            ("SCORE", 16.0),
+            ("LINES", 13.0),
+            (if timer.is_some() { "TIME" } else { "LEVEL" }, 10.0),
+            ("SEED", 7.0),
+            ("PRESS", 4.0),
+            ("ENTER", 3.0),
+        ] {
+            let mut text = Text::new(label, textures);
+            text.set_position(Vector2::new(CENTER_X, y));
+            labels.push(text);
+        }
+
+        let mut score = ScoreNumber::new(game.score(), score_display, textures.digits(), window);
+        score.set_position(Vector2::new(CENTER_X, 15.0));
+
+        let mut lines = Number::new(game.total_lines(), textures.digits(), window);
+        lines.set_position(Vector2::new(CENTER_X, 12.0));
+
+        let (level, time) = match timer {
+            Some(frames) => {
+                let mut time = Text::time(frames, textures);
+                time.set_position(Vector2::new(CENTER_X, 9.0));
+                (None, Some(time))
+            }
+            None => {
+                let mut level = Number::new(0, textures.digits(), window);
+                level.set_digits(level_display.digits(game.level()), textures.hex_digits());
+                level.set_position(Vector2::new(CENTER_X, 9.0));
+                (Some(level), None)
+            }
+        };
+
+        let mut seed = Text::new(&game.seed().to_string(), textures);
+        seed.set_position(Vector2::new(CENTER_X, 6.0));
+
+        GameOverUI {
+            labels: labels.into_boxed_slice(),
+            score,
+            lines,
+            level,
+            time,
+            seed,
+        }
+    }
+
+    pub fn render<I: Input>(&mut self, window: &mut Window<I>) {
+        for label in self.labels.iter_mut() {
+            label.render(window);
+        }
+
+        self.score.render(window);
+        self.lines.render(window);
+        if let Some(level) = &mut self.level {
+            level.render(window);
+        }
+        if let Some(time) = &mut self.time {
+            time.render(window);
+        }
+        self.seed.render(window);
+    }
+}
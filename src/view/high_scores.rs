@@ -0,0 +1,63 @@
+use super::{Text, Textures};
+use crate::{
+    high_scores::{HighScore, HighScores, MAX_HIGH_SCORES},
+    model::BOARD_WIDTH,
+};
+use colosseum::{Input, Vector2, Window};
+
+pub struct HighScoresUI {
+    labels: Box<[Text]>,
+    rows: Box<[Option<Text>]>,
+}
+
+const CENTER_X: f32 = BOARD_WIDTH as f32 / 2.0 - 0.5;
+const FIRST_ROW_Y: f32 = 16.0;
+
+fn row_text(rank: usize, entry: &HighScore) -> String {
+    format!(
+        "{:>2} {:<6} {:>6} {:>3} {:>2}",
+        rank + 1,
+        entry.name,
+        entry.score,
+        entry.lines,
+        entry.starting_level
+    )
+}
+
+impl HighScoresUI {
+    pub fn new(high_scores: &HighScores, textures: &Textures) -> Self {
+        let mut labels = Vec::with_capacity(3);
+        for (label, y) in [("HIGH SCORES", 18.0), ("TYPE NAME", 4.0), ("PRESS ENTER", 3.0)] {
+            let mut text = Text::new(label, textures);
+            text.set_position(Vector2::new(CENTER_X, y));
+            labels.push(text);
+        }
+
+        let mut ui = HighScoresUI {
+            labels: labels.into_boxed_slice(),
+            rows: (0..MAX_HIGH_SCORES).map(|_| None).collect(),
+        };
+
+        for (rank, entry) in high_scores.entries().iter().enumerate() {
+            ui.set_row(rank, entry, textures);
+        }
+
+        ui
+    }
+
+    pub fn set_row(&mut self, rank: usize, entry: &HighScore, textures: &Textures) {
+        let mut text = Text::new(&row_text(rank, entry), textures);
+        text.set_position(Vector2::new(CENTER_X, FIRST_ROW_Y - rank as f32));
+        self.rows[rank] = Some(text);
+    }
+
+    pub fn render<I: Input>(&mut self, window: &mut Window<I>) {
+        for label in self.labels.iter_mut() {
+            label.render(window);
+        }
+
+        for row in self.rows.iter_mut().flatten() {
+            row.render(window);
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use super::{Number, Text, Textures};
+use crate::model::{GameMode, Ruleset, BOARD_WIDTH};
+use colosseum::{Input, StateTrackingInput, Texture, Vector2, Window};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum MenuItem {
+    Mode,
+    Level,
+    Ruleset,
+    Seed,
+}
+
+pub struct MenuUI {
+    digits: Box<[Texture]>,
+
+    labels: Box<[Text]>,
+    modes: Box<[Text]>,
+    level: Number<2>,
+    rulesets: Box<[Text]>,
+    seed_text: Text,
+
+    mode: GameMode,
+    ruleset: Ruleset,
+    seed: Option<u32>,
+    selected: MenuItem,
+    frame_counter: usize,
+}
+
+const CENTER_X: f32 = BOARD_WIDTH as f32 / 2.0 - 0.5;
+
+const BLINK_FRAMES: usize = 16;
+
+fn seed_text(seed: Option<u32>, textures: &Textures) -> Text {
+    let mut text = match seed {
+        Some(seed) => Text::new(&seed.to_string(), textures),
+        None => Text::new("RANDOM", textures),
+    };
+    text.set_position(Vector2::new(CENTER_X, 10.0));
+    text
+}
+
+impl MenuUI {
+    pub fn new(
+        mode: GameMode,
+        ruleset: Ruleset,
+        level: usize,
+        seed: Option<u32>,
+        textures: &Textures,
+        window: &mut Window<StateTrackingInput>,
+    ) -> Self {
+        let mut digits = Vec::with_capacity(10);
+        digits.extend(textures.digits().iter().map(|texture| texture.clone()));
+
+        let mut labels = Vec::with_capacity(10);
+        for (label, y) in [
+            ("QUADRIS", 19.0),
+            ("MODE", 17.0),
+            ("LEVEL", 15.0),
+            ("RULES", 13.0),
+            ("SEED", 11.0),
+            ("PRESS", 8.0),
+            ("ENTER", 7.0),
+            ("R REPLAY", 4.0),
+            ("K KEYS", 3.0),
+            ("V VERSUS", 2.0),
+        ] {
+            let mut text = Text::new(label, textures);
+            text.set_position(Vector2::new(CENTER_X, y));
+            labels.push(text);
+        }
+
+        let modes = GameMode::ALL
+            .iter()
+            .map(|mode| {
+                let name = mode.name().replace('_', " ").to_ascii_uppercase();
+                let mut text = Text::new(&name, textures);
+                text.set_position(Vector2::new(CENTER_X, 16.0));
+                text
+            })
+            .collect();
+
+        let mut number = Number::new(level, textures.digits(), window);
+        number.set_position(Vector2::new(CENTER_X, 14.0));
+
+        let rulesets = Ruleset::ALL
+            .iter()
+            .map(|ruleset| {
+                let mut text = Text::new(&ruleset.name().to_ascii_uppercase(), textures);
+                text.set_position(Vector2::new(CENTER_X, 12.0));
+                text
+            })
+            .collect();
+
+        MenuUI {
+            digits: digits.into_boxed_slice(),
+            labels: labels.into_boxed_slice(),
+            modes,
+            level: number,
+            rulesets,
+            seed_text: seed_text(seed, textures),
+            mode,
+            ruleset,
+            seed,
+            selected: MenuItem::Mode,
+            frame_counter: 0,
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        mode: GameMode,
+        ruleset: Ruleset,
+        level: usize,
+        seed: Option<u32>,
+        selected: MenuItem,
+        textures: &Textures,
+    ) {
+        self.mode = mode;
+        self.ruleset = ruleset;
+
+        if self.seed != seed {
+            self.seed = seed;
+            self.seed_text = seed_text(seed, textures);
+        }
+        self.level.set_value(level, &self.digits);
+
+        if self.selected != selected {
+            self.selected = selected;
+            self.frame_counter = 0;
+        } else {
+            self.frame_counter += 1;
+        }
+    }
+
+    pub fn render<I: Input>(&mut self, window: &mut Window<I>) {
+        for label in self.labels.iter_mut() {
+            label.render(window);
+        }
+
+        // The selected item blinks
+        let blink = (self.frame_counter / BLINK_FRAMES) % 2 == 1;
+
+        if !(blink && self.selected == MenuItem::Mode) {
+            self.modes[self.mode as usize].render(window);
+        }
+
+        if !(blink && self.selected == MenuItem::Level) {
+            self.level.render(window);
+        }
+
+        if !(blink && self.selected == MenuItem::Ruleset) {
+            self.rulesets[self.ruleset as usize].render(window);
+        }
+
+        if !(blink && self.selected == MenuItem::Seed) {
+            self.seed_text.render(window);
+        }
+    }
+}
@@ -1,3 +1,21 @@
+mod controls;
+mod game_over;
+mod high_scores;
+mod menu;
+mod number;
+mod paused;
 mod playing;
+mod text;
+mod textures;
+mod versus;
 
+pub use controls::*;
+pub use game_over::*;
+pub use high_scores::*;
+pub use menu::*;
+pub use number::*;
+pub use paused::*;
 pub use playing::*;
+pub use text::*;
+pub use textures::*;
+pub use versus::*;
@@ -0,0 +1,131 @@
+use crate::config::ScoreDisplay;
+use colosseum::{Input, Sprite, Texture, Vector2, Window};
+
+pub struct Number<const DIGITS: usize> {
+    digits: Box<[Sprite]>,
+    shown: [usize; DIGITS],
+}
+
+const fn max_value(digits: usize) -> usize {
+    10usize.pow(digits as u32) - 1
+}
+
+fn offset(digits: usize) -> f32 {
+    ((digits - 1) as f32) / 2.0
+}
+
+// Values too big for the counter show as all nines
+fn decimal_digits<const DIGITS: usize>(value: usize) -> [usize; DIGITS] {
+    let mut value = value.min(max_value(DIGITS));
+    let mut digits = [0; DIGITS];
+    for digit in digits.iter_mut().rev() {
+        *digit = value % 10;
+        value /= 10;
+    }
+    digits
+}
+
+impl<const DIGITS: usize> Number<DIGITS> {
+    pub fn new<I: Input>(
+        initial_value: usize,
+        digit_textures: &[Texture],
+        window: &mut Window<I>,
+    ) -> Self {
+        let shown = decimal_digits(initial_value);
+
+        let offset = offset(DIGITS);
+        let digits = shown
+            .iter()
+            .enumerate()
+            .map(|(i, digit)| {
+                let mut sprite = Sprite::new(Some(digit_textures[*digit].clone()));
+                sprite
+                    .transform_mut()
+                    .set_position(Vector2::new(i as f32 - offset, 0.0));
+                sprite
+            })
+            .collect();
+
+        Number { digits, shown }
+    }
+
+    pub fn set_position(&mut self, position: Vector2) {
+        let offset = offset(DIGITS);
+        for i in 0..DIGITS {
+            self.digits[i]
+                .transform_mut()
+                .set_position(Vector2::new(position.x() + i as f32 - offset, position.y()));
+        }
+    }
+
+    pub fn set_value(&mut self, value: usize, digit_textures: &[Texture]) {
+        self.set_digits(decimal_digits(value), digit_textures);
+    }
+
+    /// Shows each digit as its texture in `digit_textures`, so counters that
+    /// aren't decimal can pass more than ten
+    pub fn set_digits(&mut self, digits: [usize; DIGITS], digit_textures: &[Texture]) {
+        for i in 0..DIGITS {
+            if digits[i] != self.shown[i] {
+                self.digits[i].set_texture(Some(digit_textures[digits[i]].clone()));
+            }
+        }
+
+        self.shown = digits;
+    }
+
+    pub fn render<I: Input>(&mut self, window: &mut Window<I>) {
+        for digit in self.digits.iter_mut() {
+            digit.render(window);
+        }
+    }
+}
+
+/// The score counter, with six digits or seven depending on the setting
+pub enum ScoreNumber {
+    Classic(Number<6>),
+    Modern(Number<7>),
+}
+
+impl ScoreNumber {
+    pub fn new<I: Input>(
+        initial_value: usize,
+        display: ScoreDisplay,
+        digit_textures: &[Texture],
+        window: &mut Window<I>,
+    ) -> Self {
+        match display {
+            ScoreDisplay::Classic => {
+                ScoreNumber::Classic(Number::new(initial_value, digit_textures, window))
+            }
+            ScoreDisplay::Modern => {
+                ScoreNumber::Modern(Number::new(initial_value, digit_textures, window))
+            }
+        }
+    }
+
+    /// Centres the counter on `position`, half a cell to the left with seven
+    /// digits so they stay on the tile grid
+    pub fn set_position(&mut self, position: Vector2) {
+        match self {
+            ScoreNumber::Classic(number) => number.set_position(position),
+            ScoreNumber::Modern(number) => {
+                number.set_position(Vector2::new(position.x() - 0.5, position.y()))
+            }
+        }
+    }
+
+    pub fn set_value(&mut self, value: usize, digit_textures: &[Texture]) {
+        match self {
+            ScoreNumber::Classic(number) => number.set_value(value, digit_textures),
+            ScoreNumber::Modern(number) => number.set_value(value, digit_textures),
+        }
+    }
+
+    pub fn render<I: Input>(&mut self, window: &mut Window<I>) {
+        match self {
+            ScoreNumber::Classic(number) => number.render(window),
+            ScoreNumber::Modern(number) => number.render(window),
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use super::{Text, Textures};
+use crate::model::{BOARD_HEIGHT, BOARD_WIDTH};
+use colosseum::{Input, Vector2, Window};
+
+pub struct PausedUI {
+    text: Text,
+}
+
+impl PausedUI {
+    pub fn new(textures: &Textures) -> Self {
+        let mut text = Text::new("PAUSE", textures);
+        text.set_position(Vector2::new(
+            BOARD_WIDTH as f32 / 2.0 - 0.5,
+            BOARD_HEIGHT as f32 / 2.0 + 0.5,
+        ));
+
+        PausedUI { text }
+    }
+
+    pub fn render<I: Input>(&mut self, window: &mut Window<I>) {
+        self.text.render(window);
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{
+    model::{Clear, TSpin},
+    view::{Text, Textures},
+};
+use colosseum::{Input, Vector2, Window};
+
+/// Names a notable clear for a moment, under the hold box
+pub struct Announcement {
+    lines: Box<[Text]>,
+    frames_left: usize,
+}
+
+const SHOW_FRAMES: usize = 90;
+const POSITION: (f32, f32) = (-12.5, 12.0);
+
+// Plain singles, doubles and triples aren't worth announcing
+fn describe(event: &Clear) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    if event.perfect_clear {
+        lines.push("PERFECT".to_owned());
+        lines.push("CLEAR".to_owned());
+    }
+
+    if event.back_to_back {
+        lines.push("B2B".to_owned());
+    }
+
+    match event.t_spin {
+        TSpin::None => {}
+        TSpin::Mini => {
+            lines.push("MINI".to_owned());
+            lines.push("T SPIN".to_owned());
+        }
+        TSpin::Full => lines.push("T SPIN".to_owned()),
+    }
+
+    let notable = !lines.is_empty() || event.lines >= 4 || event.combo > 0;
+    if !notable {
+        return lines;
+    }
+
+    match event.lines {
+        0 => {}
+        1 => lines.push("SINGLE".to_owned()),
+        2 => lines.push("DOUBLE".to_owned()),
+        3 => lines.push("TRIPLE".to_owned()),
+        _ => lines.push("TETRIS".to_owned()),
+    }
+
+    if event.combo > 0 {
+        lines.push(format!("COMBO {}", event.combo));
+    }
+
+    lines
+}
+
+impl Announcement {
+    pub fn new() -> Self {
+        Announcement {
+            lines: Box::new([]),
+            frames_left: 0,
+        }
+    }
+
+    /// Replaces whatever is showing with `event`, if it is worth announcing
+    pub fn show(&mut self, event: &Clear, textures: &Textures) {
+        let lines = describe(event);
+        if lines.is_empty() {
+            return;
+        }
+
+        self.lines = lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                let mut text = Text::new(line, textures);
+                text.set_position(Vector2::new(POSITION.0, POSITION.1 - i as f32));
+                text
+            })
+            .collect();
+        self.frames_left = SHOW_FRAMES;
+    }
+
+    pub fn update(&mut self) {
+        self.frames_left = self.frames_left.saturating_sub(1);
+    }
+
+    pub fn render<I: Input>(&mut self, window: &mut Window<I>) {
+        if self.frames_left == 0 {
+            return;
+        }
+
+        for line in self.lines.iter_mut() {
+            line.render(window);
+        }
+    }
+}
@@ -1,16 +1,27 @@
-use super::Textures;
+use crate::view::Textures;
 use colosseum::{Input, Sprite, Vector2, Window};
 
 pub struct Background {
     left_pane: Sprite,
     right_pane: Sprite,
 
-    left_tiles: Sprite,
-    right_tiles: Sprite,
+    // Tiles filling the screen either side of the panes, left out when
+    // something else shares the screen
+    left_tiles: Option<Sprite>,
+    right_tiles: Option<Sprite>,
+
+    hold_box: Option<Sprite>,
+    next_queue: Option<Sprite>,
 }
 
 impl Background {
-    pub fn new<I: Input>(textures: &Textures, window: &mut Window<I>) -> Self {
+    pub fn new<I: Input>(
+        textures: &Textures,
+        hold: bool,
+        next_queue: bool,
+        fill_screen: bool,
+        window: &mut Window<I>,
+    ) -> Self {
         let mut left_pane = Sprite::new(Some(textures.background_left().clone()));
         let mut right_pane = Sprite::new(Some(textures.background_right().clone()));
 
@@ -66,18 +77,67 @@ impl Background {
             .transform_mut()
             .set_scale(Vector2::new(right_width, 20.0));
 
+        // The hold box sits against the left pane, level with the preview
+        let hold_box = if hold {
+            let mut hold_box = Sprite::new(Some(textures.hold_box().clone()));
+            hold_box
+                .transform_mut()
+                .set_position(Vector2::new(-12.5, 17.0));
+            hold_box.transform_mut().set_scale(Vector2::new(8.0, 7.0));
+            hold_box.transform_mut().set_z_order(1.0);
+            Some(hold_box)
+        } else {
+            None
+        };
+
+        // Pieces past the first in the queue go in a column right of the
+        // right pane
+        let next_queue = if next_queue {
+            let mut next_queue = Sprite::new(Some(textures.next_queue().clone()));
+            next_queue
+                .transform_mut()
+                .set_position(Vector2::new(20.5, 10.5));
+            next_queue
+                .transform_mut()
+                .set_scale(Vector2::new(6.0, 20.0));
+            next_queue.transform_mut().set_z_order(1.0);
+            Some(next_queue)
+        } else {
+            None
+        };
+
+        let (left_tiles, right_tiles) = if fill_screen {
+            (Some(left_tiles), Some(right_tiles))
+        } else {
+            (None, None)
+        };
+
         Background {
             left_pane,
             right_pane,
             left_tiles,
             right_tiles,
+            hold_box,
+            next_queue,
         }
     }
 
     pub fn render<I: Input>(&mut self, window: &mut Window<I>) {
-        self.left_tiles.render(window);
-        self.right_tiles.render(window);
+        if let Some(left_tiles) = &mut self.left_tiles {
+            left_tiles.render(window);
+        }
+        if let Some(right_tiles) = &mut self.right_tiles {
+            right_tiles.render(window);
+        }
         self.left_pane.render(window);
         self.right_pane.render(window);
+
+        if let Some(hold_box) = &mut self.hold_box {
+            hold_box.render(window);
+        }
+
+        if let Some(next_queue) = &mut self.next_queue {
+            next_queue.render(window);
+        }
     }
 }
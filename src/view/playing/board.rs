@@ -0,0 +1,53 @@
+use super::tile::{tile_color, tile_position};
+use crate::model::{Board, TileColor, BOARD_HEIGHT, BOARD_WIDTH};
+use colosseum::{Input, Sprite, Texture, Window};
+
+pub struct BoardView {
+    tiles: Box<[Sprite]>,
+    colors: Box<[Option<TileColor>]>,
+}
+
+impl BoardView {
+    pub fn new(texture: &Texture) -> Self {
+        let mut tiles = Vec::with_capacity(BOARD_WIDTH * BOARD_HEIGHT);
+        for y in 0..BOARD_HEIGHT {
+            for x in 0..BOARD_WIDTH {
+                let mut sprite = Sprite::new(Some(texture.clone()));
+                sprite
+                    .transform_mut()
+                    .set_position(tile_position((x as isize, y as isize)));
+                tiles.push(sprite);
+            }
+        }
+
+        BoardView {
+            tiles: tiles.into_boxed_slice(),
+            colors: vec![None; BOARD_WIDTH * BOARD_HEIGHT].into_boxed_slice(),
+        }
+    }
+
+    pub fn update(&mut self, board: &Board) {
+        for y in 0..BOARD_HEIGHT {
+            for x in 0..BOARD_WIDTH {
+                let i = x + y * BOARD_WIDTH;
+                let color = board.get(x as isize, y as isize).unwrap();
+                if color == self.colors[i] {
+                    continue;
+                }
+
+                if let Some(color) = color {
+                    self.tiles[i].set_tint(tile_color(color));
+                }
+                self.colors[i] = color;
+            }
+        }
+    }
+
+    pub fn render<I: Input>(&mut self, window: &mut Window<I>) {
+        for (tile, color) in self.tiles.iter_mut().zip(self.colors.iter()) {
+            if color.is_some() {
+                tile.render(window);
+            }
+        }
+    }
+}
@@ -1,52 +1,108 @@
-use self::{background::Background, number::Number};
-use crate::model::Game;
+use self::{
+    announcement::Announcement, background::Background, board::BoardView, piece::PieceView,
+    timer::TimerView,
+};
+use super::{Number, ScoreNumber, Textures};
+use crate::{
+    config::{LevelDisplay, ScoreDisplay},
+    model::{Event, Game, Piece},
+};
 use colosseum::{Input, StateTrackingInput, Texture, Vector2, Window};
 
+mod announcement;
 mod background;
-mod number;
-mod textures;
+mod board;
+mod piece;
+mod tile;
+mod timer;
+
+const PREVIEW_POSITION: (isize, isize) = (-5, 4);
+const HOLD_POSITION: (isize, isize) = (-13, 4);
+
+// The rest of the next queue is drawn down a column, one piece every few rows
+const QUEUE_POSITION: (isize, isize) = (20, 2);
+const QUEUE_SPACING: isize = 3;
+
+// Timed modes show their clock in the empty rows under the level
+const TIMER_POSITION: (f32, f32) = (13.5, 2.0);
+
+fn next_piece_position(index: usize) -> (isize, isize) {
+    if index == 0 {
+        PREVIEW_POSITION
+    } else {
+        (
+            QUEUE_POSITION.0,
+            QUEUE_POSITION.1 + (index as isize - 1) * QUEUE_SPACING,
+        )
+    }
+}
 
-pub use textures::*;
+// Modes with a line goal count their lines down instead of up
+fn lines_total(game: &Game) -> usize {
+    game.lines_remaining().unwrap_or(game.total_lines())
+}
 
 pub struct PlayingUI {
     digits: Box<[Texture]>,
+    hex_digits: Box<[Texture]>,
+    level_display: LevelDisplay,
 
     background: Background,
 
-    score: Number<6>,
-    top_score: Number<6>,
+    board: BoardView,
+    current_piece: PieceView,
+    ghost_piece: PieceView,
+    next_pieces: Box<[PieceView]>,
+    held_piece: PieceView,
+
+    score: ScoreNumber,
+    top: usize,
+    top_score: ScoreNumber,
     lines_level: Number<3>,
     lines_total: Number<6>,
     level: Number<2>,
+    timer: Option<TimerView>,
+    announcement: Announcement,
 
     stats: Box<[Number<3>]>,
 }
 
 impl PlayingUI {
+    /// Lays out the HUD around a board at the origin, filling the rest of the
+    /// screen with tiles if `fill_screen` is set
     pub fn new(
         game: &Game,
         top_score: usize,
+        level_display: LevelDisplay,
+        score_display: ScoreDisplay,
+        fill_screen: bool,
         textures: &Textures,
         window: &mut Window<StateTrackingInput>,
     ) -> Self {
         let mut digits = Vec::with_capacity(10);
         digits.extend(textures.digits().iter().map(|texture| texture.clone()));
 
-        let mut score = Number::new(game.score(), textures.digits(), window);
+        let mut score = ScoreNumber::new(game.score(), score_display, textures.digits(), window);
         score.set_position(Vector2::new(13.5, 18.0));
 
-        let mut top_score = Number::new(top_score, textures.digits(), window);
-        top_score.set_position(Vector2::new(13.5, 16.0));
+        let mut top_score_number =
+            ScoreNumber::new(top_score, score_display, textures.digits(), window);
+        top_score_number.set_position(Vector2::new(13.5, 16.0));
 
         let mut lines_level = Number::new(game.level_lines(), textures.digits(), window);
         lines_level.set_position(Vector2::new(13.5, 11.0));
 
-        let mut lines_total = Number::new(game.total_lines(), textures.digits(), window);
+        let mut lines_total = Number::new(lines_total(game), textures.digits(), window);
         lines_total.set_position(Vector2::new(13.5, 9.0));
 
         let mut level = Number::new(game.level(), textures.digits(), window);
         level.set_position(Vector2::new(13.5, 5.0));
 
+        let timer = game.timer().map(|frames| {
+            let position = Vector2::new(TIMER_POSITION.0, TIMER_POSITION.1);
+            TimerView::new(frames, position, textures, window)
+        });
+
         let mut stats = Vec::with_capacity(7);
         let mut y = 11.5;
         for stat in game.stats() {
@@ -56,37 +112,119 @@ impl PlayingUI {
             y -= 1.5;
         }
 
-        PlayingUI {
+        let mut ui = PlayingUI {
             digits: digits.into_boxed_slice(),
-            background: Background::new(textures, window),
+            hex_digits: textures.hex_digits().into(),
+            level_display,
+            background: Background::new(
+                textures,
+                game.ruleset().hold(),
+                game.next_queue().len() > 1,
+                fill_screen,
+                window,
+            ),
+            board: BoardView::new(textures.tile()),
+            current_piece: PieceView::new(textures.tile()),
+            ghost_piece: PieceView::ghost(textures.tile()),
+            next_pieces: game
+                .next_queue()
+                .iter()
+                .map(|_| PieceView::new(textures.tile()))
+                .collect(),
+            held_piece: PieceView::new(textures.tile()),
             score,
-            top_score,
+            top: top_score,
+            top_score: top_score_number,
             lines_level,
             lines_total,
             level,
+            timer,
+            announcement: Announcement::new(),
             stats: stats.into_boxed_slice(),
-        }
+        };
+        ui.update_lines(game);
+        ui.update(game);
+        ui
     }
 
+    /// Keeps the board and pieces in step with `game`, once a frame
     pub fn update(&mut self, game: &Game) {
-        self.score.set_value(game.score(), &self.digits);
-        self.lines_total.set_value(game.total_lines(), &self.digits);
-        self.lines_level.set_value(game.level_lines(), &self.digits);
-        self.level.set_value(game.level(), &self.digits);
+        self.board.update(game.board());
+        self.current_piece.update(game.current_piece());
+        self.ghost_piece.update(game.ghost_piece().as_ref());
+        for (i, (view, class)) in self
+            .next_pieces
+            .iter_mut()
+            .zip(game.next_queue())
+            .enumerate()
+        {
+            view.update_at(&Piece::new(*class), next_piece_position(i));
+        }
+        match game.held_piece() {
+            Some(held_piece) => self.held_piece.update_at(held_piece, HOLD_POSITION),
+            None => self.held_piece.update(None),
+        }
+
+        if let (Some(timer), Some(frames)) = (self.timer.as_mut(), game.timer()) {
+            timer.update(frames, &self.digits);
+        }
+
+        self.announcement.update();
+    }
 
-        let stats = game.stats();
-        for i in 0..7 {
-            self.stats[i].set_value(stats[i], &self.digits);
+    /// Updates the counters `event` changed, where `game` is the state after
+    /// it
+    pub fn handle(&mut self, event: &Event, game: &Game, textures: &Textures) {
+        match event {
+            Event::Spawned(_) => {
+                let stats = game.stats();
+                for i in 0..7 {
+                    self.stats[i].set_value(stats[i], &self.digits);
+                }
+            }
+            Event::Locked { clear, .. } => self.announcement.show(clear, textures),
+            Event::LinesCleared { .. } | Event::LevelUp(_) | Event::GarbageAdded(_) => {
+                self.update_lines(game)
+            }
+            Event::ScoreChanged(score) => {
+                self.score.set_value(*score, &self.digits);
+                self.top_score.set_value(self.top.max(*score), &self.digits);
+            }
+            _ => {}
         }
     }
 
+    fn update_lines(&mut self, game: &Game) {
+        self.lines_total.set_value(lines_total(game), &self.digits);
+        self.lines_level.set_value(game.level_lines(), &self.digits);
+        self.level
+            .set_digits(self.level_display.digits(game.level()), &self.hex_digits);
+    }
+
     pub fn render<I: Input>(&mut self, window: &mut Window<I>) {
+        self.render_hud(window);
+        self.announcement.render(window);
+
+        self.board.render(window);
+        self.ghost_piece.render(window);
+        self.current_piece.render(window);
+        for next_piece in self.next_pieces.iter_mut() {
+            next_piece.render(window);
+        }
+        self.held_piece.render(window);
+    }
+
+    /// Renders everything but the board and the pieces
+    pub fn render_hud<I: Input>(&mut self, window: &mut Window<I>) {
         self.background.render(window);
         self.score.render(window);
         self.top_score.render(window);
         self.lines_level.render(window);
         self.lines_total.render(window);
         self.level.render(window);
+        if let Some(timer) = &mut self.timer {
+            timer.render(window);
+        }
 
         for stat in self.stats.iter_mut() {
             stat.render(window);
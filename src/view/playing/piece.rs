@@ -0,0 +1,65 @@
+use super::tile::{shaded_tile_color, tile_position};
+use crate::model::{Piece, PieceClass};
+use colosseum::{Input, Sprite, Texture, Window};
+
+pub struct PieceView {
+    tiles: [Sprite; 4],
+    class: Option<PieceClass>,
+    brightness: f32,
+}
+
+const GHOST_BRIGHTNESS: f32 = 0.3;
+
+impl PieceView {
+    pub fn new(texture: &Texture) -> Self {
+        PieceView::with_brightness(texture, 1.0)
+    }
+
+    /// A darkened piece used to show where the current piece will land
+    pub fn ghost(texture: &Texture) -> Self {
+        PieceView::with_brightness(texture, GHOST_BRIGHTNESS)
+    }
+
+    fn with_brightness(texture: &Texture, brightness: f32) -> Self {
+        PieceView {
+            tiles: std::array::from_fn(|_| Sprite::new(Some(texture.clone()))),
+            class: None,
+            brightness,
+        }
+    }
+
+    /// Mirrors `piece` at its position on the board, hiding it when there is none
+    pub fn update(&mut self, piece: Option<&Piece>) {
+        match piece {
+            Some(piece) => self.update_at(piece, piece.position()),
+            None => self.class = None,
+        }
+    }
+
+    /// Mirrors `piece` as if it were at `position`, used for previews outside the board
+    pub fn update_at(&mut self, piece: &Piece, position: (isize, isize)) {
+        if self.class != Some(piece.class()) {
+            for tile in &mut self.tiles {
+                tile.set_tint(shaded_tile_color(piece.color(), self.brightness));
+            }
+            self.class = Some(piece.class());
+        }
+
+        for i in 0..4 {
+            let offset = piece.get_tile_offset(i);
+            self.tiles[i]
+                .transform_mut()
+                .set_position(tile_position((position.0 + offset.0, position.1 + offset.1)));
+        }
+    }
+
+    pub fn render<I: Input>(&mut self, window: &mut Window<I>) {
+        if self.class.is_none() {
+            return;
+        }
+
+        for tile in &mut self.tiles {
+            tile.render(window);
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use crate::model::{TileColor, BOARD_HEIGHT};
+use colosseum::{Vector2, Vector4};
+
+pub fn tile_position(position: (isize, isize)) -> Vector2 {
+    Vector2::new(position.0 as f32, BOARD_HEIGHT as f32 - position.1 as f32)
+}
+
+pub fn tile_color(color: TileColor) -> Vector4 {
+    shaded_tile_color(color, 1.0)
+}
+
+/// The tint for `color` with its channels scaled by `brightness`
+pub fn shaded_tile_color(color: TileColor, brightness: f32) -> Vector4 {
+    let (red, green, blue) = match color {
+        TileColor::Red => (1.0, 0.0, 0.0),
+        TileColor::Orange => (1.0, 0.5, 0.0),
+        TileColor::Yellow => (1.0, 1.0, 0.0),
+        TileColor::Green => (0.0, 1.0, 0.0),
+        TileColor::Cyan => (0.0, 1.0, 1.0),
+        TileColor::Blue => (0.0, 0.0, 1.0),
+        TileColor::Purple => (0.5, 0.0, 0.5),
+        TileColor::Garbage => (0.5, 0.5, 0.5),
+    };
+
+    Vector4::new(red * brightness, green * brightness, blue * brightness, 1.0)
+}
@@ -0,0 +1,71 @@
+use crate::{
+    model::FRAMES_PER_SECOND,
+    view::{Number, Text, Textures},
+};
+use colosseum::{Input, Sprite, Texture, Vector2, Window};
+
+/// A labelled time counted in frames, shown as seconds to the hundredth
+pub struct TimerView {
+    label: Text,
+    seconds: Number<3>,
+    period: Sprite,
+    hundredths: Number<2>,
+}
+
+// The time is drawn as "SSS.CC", centred on the given position
+const MAX_SECONDS: usize = 999;
+
+fn split(frames: usize) -> (usize, usize) {
+    let seconds = frames / FRAMES_PER_SECOND;
+    if seconds > MAX_SECONDS {
+        (MAX_SECONDS, 99)
+    } else {
+        (seconds, frames % FRAMES_PER_SECOND * 100 / FRAMES_PER_SECOND)
+    }
+}
+
+impl TimerView {
+    pub fn new<I: Input>(
+        frames: usize,
+        position: Vector2,
+        textures: &Textures,
+        window: &mut Window<I>,
+    ) -> Self {
+        let (x, y) = (position.x(), position.y());
+        let (seconds, hundredths) = split(frames);
+
+        let mut label = Text::new("TIME", textures);
+        label.set_position(Vector2::new(x, y + 1.0));
+
+        let mut seconds = Number::new(seconds, textures.digits(), window);
+        seconds.set_position(Vector2::new(x - 1.5, y));
+
+        let mut period = Sprite::new(Some(textures.period().clone()));
+        period
+            .transform_mut()
+            .set_position(Vector2::new(x + 0.5, y));
+
+        let mut hundredths = Number::new(hundredths, textures.digits(), window);
+        hundredths.set_position(Vector2::new(x + 2.0, y));
+
+        TimerView {
+            label,
+            seconds,
+            period,
+            hundredths,
+        }
+    }
+
+    pub fn update(&mut self, frames: usize, digits: &[Texture]) {
+        let (seconds, hundredths) = split(frames);
+        self.seconds.set_value(seconds, digits);
+        self.hundredths.set_value(hundredths, digits);
+    }
+
+    pub fn render<I: Input>(&mut self, window: &mut Window<I>) {
+        self.label.render(window);
+        self.seconds.render(window);
+        self.period.render(window);
+        self.hundredths.render(window);
+    }
+}
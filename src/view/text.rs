@@ -0,0 +1,58 @@
+use super::Textures;
+use crate::model::FRAMES_PER_SECOND;
+use colosseum::{Input, Sprite, Vector2, Window};
+
+pub struct Text {
+    characters: Box<[Option<Sprite>]>,
+}
+
+fn offset(length: usize) -> f32 {
+    (length.max(1) - 1) as f32 / 2.0
+}
+
+impl Text {
+    /// Creates a line of text from upper-case letters, digits, periods and
+    /// spaces
+    pub fn new(text: &str, textures: &Textures) -> Self {
+        let characters = text
+            .bytes()
+            .map(|character| {
+                let texture = match character {
+                    b'A'..=b'Z' => &textures.letters()[(character - b'A') as usize],
+                    b'0'..=b'9' => &textures.digits()[(character - b'0') as usize],
+                    b'.' => textures.period(),
+                    _ => return None,
+                };
+
+                Some(Sprite::new(Some(texture.clone())))
+            })
+            .collect();
+
+        Text { characters }
+    }
+
+    /// Shows a time counted in frames as seconds to the hundredth
+    pub fn time(frames: usize, textures: &Textures) -> Self {
+        let seconds = frames / FRAMES_PER_SECOND;
+        let hundredths = frames % FRAMES_PER_SECOND * 100 / FRAMES_PER_SECOND;
+        Text::new(&format!("{:03}.{:02}", seconds, hundredths), textures)
+    }
+
+    /// Centres the text horizontally on `position`
+    pub fn set_position(&mut self, position: Vector2) {
+        let offset = offset(self.characters.len());
+        for (i, character) in self.characters.iter_mut().enumerate() {
+            if let Some(character) = character {
+                character
+                    .transform_mut()
+                    .set_position(Vector2::new(position.x() + i as f32 - offset, position.y()));
+            }
+        }
+    }
+
+    pub fn render<I: Input>(&mut self, window: &mut Window<I>) {
+        for character in self.characters.iter_mut().flatten() {
+            character.render(window);
+        }
+    }
+}
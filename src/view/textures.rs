@@ -2,8 +2,13 @@ use colosseum::{Input, SampleType, Texture, Window};
 
 pub struct Textures {
     digits: Box<[Texture]>,
+    letters: Box<[Texture]>,
+    hex_digits: Box<[Texture]>,
+    period: Texture,
     background_left: Texture,
     background_right: Texture,
+    hold_box: Texture,
+    next_queue: Texture,
     tile: Texture,
 }
 
@@ -18,8 +23,23 @@ impl Textures {
             ));
         }
 
+        let mut letters = Vec::with_capacity(26);
+        for letter in b'A'..=b'Z' {
+            letters.push(Texture::load(
+                format!("./textures/{}.qoi", letter as char),
+                SampleType::Point,
+                window,
+            ));
+        }
+
+        // Counters past 9 use the letters, as the NES does
+        let hex_digits = digits.iter().chain(&letters[..6]).cloned().collect();
+
         Textures {
             digits: digits.into_boxed_slice(),
+            hex_digits,
+            letters: letters.into_boxed_slice(),
+            period: Texture::load("./textures/period.qoi", SampleType::Point, window),
             background_left: Texture::load(
                 "./textures/background_left.qoi",
                 SampleType::Point,
@@ -30,6 +50,8 @@ impl Textures {
                 SampleType::Point,
                 window,
             ),
+            hold_box: Texture::load("./textures/hold.qoi", SampleType::Point, window),
+            next_queue: Texture::load("./textures/queue.qoi", SampleType::Point, window),
             tile: Texture::load("./textures/tile.qoi", SampleType::Point, window),
         }
     }
@@ -42,6 +64,19 @@ impl Textures {
         &self.digits
     }
 
+    pub fn letters(&self) -> &[Texture] {
+        &self.letters
+    }
+
+    /// The digits 0 to 9 followed by the letters A to F
+    pub fn hex_digits(&self) -> &[Texture] {
+        &self.hex_digits
+    }
+
+    pub fn period(&self) -> &Texture {
+        &self.period
+    }
+
     pub fn background_left(&self) -> &Texture {
         &self.background_left
     }
@@ -49,4 +84,12 @@ impl Textures {
     pub fn background_right(&self) -> &Texture {
         &self.background_right
     }
+
+    pub fn hold_box(&self) -> &Texture {
+        &self.hold_box
+    }
+
+    pub fn next_queue(&self) -> &Texture {
+        &self.next_queue
+    }
 }
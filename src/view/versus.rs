@@ -0,0 +1,44 @@
+use super::{Text, Textures};
+use crate::model::{Outcome, BOARD_WIDTH};
+use colosseum::{Input, Vector2, Window};
+
+/// Shows one player how a versus round went, over their board
+pub struct VersusOverUI {
+    labels: Box<[Text]>,
+}
+
+const CENTER_X: f32 = BOARD_WIDTH as f32 / 2.0 - 0.5;
+
+impl VersusOverUI {
+    /// `wins` is the player's tally including this round
+    pub fn new(player: usize, outcome: Outcome, wins: usize, textures: &Textures) -> Self {
+        let title = match outcome {
+            Outcome::Won(winner) if winner == player => "WINNER",
+            Outcome::Won(_) => "LOSER",
+            Outcome::Draw => "DRAW",
+        };
+
+        let mut labels = Vec::with_capacity(5);
+        for (label, y) in [
+            (title, 15.0),
+            ("WINS", 12.0),
+            (wins.to_string().as_str(), 11.0),
+            ("PRESS", 8.0),
+            ("ENTER", 7.0),
+        ] {
+            let mut text = Text::new(label, textures);
+            text.set_position(Vector2::new(CENTER_X, y));
+            labels.push(text);
+        }
+
+        VersusOverUI {
+            labels: labels.into_boxed_slice(),
+        }
+    }
+
+    pub fn render<I: Input>(&mut self, window: &mut Window<I>) {
+        for label in self.labels.iter_mut() {
+            label.render(window);
+        }
+    }
+}